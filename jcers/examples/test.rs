@@ -1,5 +1,5 @@
 use bytes::Bytes;
-use jcers::JceGet;
+use jcers::{JceGet, JcePut};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq, Default, JceGet)]
@@ -18,9 +18,8 @@ pub struct TestStruct {
     pub e:   HashMap<u8, u8>,
 }
 
-// unsupported type
-// #[derive(Debug, Clone, PartialEq, Default, JceGet, JcePut)]
-// pub struct TestEnum(#[jce(0)] u8, #[jce(1)] u8);
+#[derive(Debug, Clone, PartialEq, Default, JceGet, JcePut)]
+pub struct TestTuple(#[jce(0)] u8, #[jce(1)] u8);
 
 #[derive(Debug, Clone, PartialEq, Default, JceGet)]
 pub struct TestStruct2 {
@@ -36,4 +35,6 @@ fn main() {
     println!("{:?}", t);
     let value: jcers::JceStruct = jcers::from_buf(&mut bytes).unwrap();
     println!("{:?}", value);
+    let t: TestTuple = jcers::from_buf(&mut Bytes::from_static(&[0x00, 0x01, 0x10, 0x02])).unwrap();
+    println!("{:?}", t);
 }
@@ -0,0 +1,116 @@
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+
+use bytes::{Buf, Bytes};
+
+use super::{Jce, JceError, JceGet, JceMut, JcePut, JceResult};
+
+/// UTF-8 string backed by `Bytes`
+///
+/// Decoding from a `Bytes` buf shares its memory instead of copying,
+/// the content is validated once.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteStr(Bytes);
+
+impl ByteStr {
+    pub const fn from_static(s: &'static str) -> Self {
+        ByteStr(Bytes::from_static(s.as_bytes()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        // SAFETY: the content is checked to be utf8 when constructed
+        unsafe { std::str::from_utf8_unchecked(&self.0) }
+    }
+
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.0
+    }
+
+    pub fn into_bytes(self) -> Bytes {
+        self.0
+    }
+}
+
+impl Deref for ByteStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for ByteStr {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for ByteStr {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Display for ByteStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+impl PartialEq<str> for ByteStr {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for ByteStr {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl From<String> for ByteStr {
+    fn from(s: String) -> Self {
+        ByteStr(Bytes::from(s))
+    }
+}
+
+impl From<&'static str> for ByteStr {
+    fn from(s: &'static str) -> Self {
+        Self::from_static(s)
+    }
+}
+
+impl TryFrom<Bytes> for ByteStr {
+    type Error = JceError;
+
+    fn try_from(bytes: Bytes) -> JceResult<Self> {
+        std::str::from_utf8(&bytes).map_err(|_| JceError::Utf8Error)?;
+        Ok(ByteStr(bytes))
+    }
+}
+
+impl From<ByteStr> for Bytes {
+    fn from(s: ByteStr) -> Self {
+        s.0
+    }
+}
+
+impl JceGet for ByteStr {
+    fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
+        let len = jce.string_len()?;
+        let len = jce.bytes_len(len)?;
+        ByteStr::try_from(jce.copy_to_bytes(len)?)
+    }
+
+    fn empty() -> JceResult<Self> {
+        Ok(ByteStr::default())
+    }
+}
+
+impl JcePut for ByteStr {
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        self.as_str().jce_put(jce_mut, tag)
+    }
+}
@@ -0,0 +1,351 @@
+//! Async tars RPC client, calls multiplexed over one connection
+//!
+//! Responses are matched to their calls by `request_id`, so calls can run
+//! concurrently and be answered in any order.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::io;
+use std::sync::atomic::{AtomicI32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures_util::{SinkExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+use crate::codec::JceCodec;
+use crate::tars::{RequestPacket, ResponsePacket, NORMAL, ONEWAY, SERVER_SUCCESS, TARS_VERSION};
+use crate::{JceError, JceGet, JceMut, JcePut};
+
+/// Timeout of a new `Client`, the default of tars clients
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(3);
+
+type Writer = FramedWrite<Box<dyn AsyncWrite + Send + Unpin>, JceCodec<ResponsePacket>>;
+
+/// Errors of a call
+#[derive(Debug)]
+pub enum CallError {
+    /// The connection failed or is closed
+    Io(io::Error),
+    /// No response within the timeout of the call
+    Timeout,
+    /// The server failed the call, (`ret`, `result_desc`)
+    Server(i32, String),
+    /// The results failed to decode
+    Jce(JceError),
+}
+
+impl Display for CallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Tars call io error: {}", e),
+            Self::Timeout => write!(f, "Tars call timed out"),
+            Self::Server(ret, desc) => write!(f, "Tars server error {}: {}", ret, desc),
+            Self::Jce(e) => write!(f, "Tars call decode error: {}", e),
+        }
+    }
+}
+
+impl Error for CallError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Jce(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CallError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<JceError> for CallError {
+    fn from(e: JceError) -> Self {
+        Self::Jce(e)
+    }
+}
+
+/// Calls waiting for their response, by request id
+#[derive(Default)]
+struct Pending {
+    calls: HashMap<i32, oneshot::Sender<ResponsePacket>>,
+    /// why the connection closed, once it is
+    closed: Option<(io::ErrorKind, String)>,
+}
+
+impl Pending {
+    /// Error of calls made after the connection closed
+    fn closed_error(&self) -> Option<io::Error> {
+        self.closed
+            .as_ref()
+            .map(|(kind, e)| io::Error::new(*kind, e.clone()))
+    }
+}
+
+/// Takes the call off `Pending` once it is answered or given up
+struct Unregister<'a> {
+    pending: &'a Mutex<Pending>,
+    id: i32,
+}
+
+impl Drop for Unregister<'_> {
+    fn drop(&mut self) {
+        self.pending.lock().unwrap().calls.remove(&self.id);
+    }
+}
+
+struct Inner {
+    writer: Arc<tokio::sync::Mutex<Writer>>,
+    pending: Arc<Mutex<Pending>>,
+    next_id: AtomicI32,
+    reader: JoinHandle<()>,
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// Answer the calls of `pending` with the responses of `reader`, until the
+/// connection closes or a frame fails to decode
+///
+/// `writer` is then shut down, so the server sees the connection end too.
+async fn read_responses<R>(
+    mut reader: FramedRead<R, JceCodec<ResponsePacket>>,
+    pending: Arc<Mutex<Pending>>,
+    writer: Arc<tokio::sync::Mutex<Writer>>,
+) where
+    R: AsyncRead + Unpin,
+{
+    let e = loop {
+        match reader.next().await {
+            Some(Ok(response)) => {
+                let call = pending.lock().unwrap().calls.remove(&response.request_id);
+                // responses of calls given up on are dropped
+                if let Some(call) = call {
+                    let _ = call.send(response);
+                }
+            }
+            Some(Err(e)) => break e,
+            None => break io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed"),
+        }
+    };
+    {
+        let mut pending = pending.lock().unwrap();
+        pending.closed = Some((e.kind(), e.to_string()));
+        // waiting calls see their sender dropped
+        pending.calls.clear();
+    }
+    let _ = SinkExt::<RequestPacket>::close(&mut *writer.lock().await).await;
+}
+
+/// Tars RPC client over one connection
+///
+/// Clones share the connection, which is closed once they are all dropped.
+///
+/// ## Example
+/// ```rust,no_run
+/// # async fn hello() -> Result<(), jcers::client::CallError> {
+/// let client = jcers::client::Client::connect("127.0.0.1:10000").await?;
+/// let greeting: String = client
+///     .call("Test.HelloServer.HelloObj", "hello", "world")
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Client {
+    inner: Arc<Inner>,
+    timeout: Duration,
+}
+
+impl Client {
+    /// Connect to a tars server
+    pub async fn connect<A>(addr: A) -> io::Result<Self>
+    where
+        A: ToSocketAddrs,
+    {
+        let stream = TcpStream::connect(addr).await?;
+        stream.set_nodelay(true)?;
+        Ok(Client::new(stream))
+    }
+
+    /// Client over `stream`, with a task reading the responses
+    ///
+    /// Must be called within a tokio runtime.
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (read, write) = tokio::io::split(stream);
+        let pending = Arc::new(Mutex::new(Pending::default()));
+        let write: Box<dyn AsyncWrite + Send + Unpin> = Box::new(write);
+        let writer = Arc::new(tokio::sync::Mutex::new(FramedWrite::new(
+            write,
+            JceCodec::new(),
+        )));
+        let reader = tokio::spawn(read_responses(
+            FramedRead::new(read, JceCodec::new()),
+            pending.clone(),
+            writer.clone(),
+        ));
+        Client {
+            inner: Arc::new(Inner {
+                writer,
+                pending,
+                next_id: AtomicI32::new(1),
+                reader,
+            }),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Timeout of the calls of this client, `DEFAULT_TIMEOUT` by default
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    /// Call `func` of `servant` with `req` as its argument, and decode its
+    /// return value
+    ///
+    /// As in tars, the argument is written at tag 1 of the `buffer` and the
+    /// return value read at tag 0. A `ret` other than `SERVER_SUCCESS` is a
+    /// `CallError::Server`.
+    pub async fn call<Req, Resp>(
+        &self,
+        servant: &str,
+        func: &str,
+        req: Req,
+    ) -> Result<Resp, CallError>
+    where
+        Req: JcePut,
+        Resp: JceGet,
+    {
+        let response = self.invoke(self.request(servant, func, req)).await?;
+        if response.ret != SERVER_SUCCESS {
+            return Err(CallError::Server(response.ret, response.result_desc));
+        }
+        let mut buffer = response.buffer;
+        Ok(crate::from_buf_with_tag(&mut buffer, 0)?)
+    }
+
+    /// Call `func` of `servant` with `req` as its argument, without response
+    pub async fn call_oneway<Req>(
+        &self,
+        servant: &str,
+        func: &str,
+        req: Req,
+    ) -> Result<(), CallError>
+    where
+        Req: JcePut,
+    {
+        self.invoke_oneway(self.request(servant, func, req)).await
+    }
+
+    /// Send `request` and wait for its response
+    ///
+    /// The `request_id` is replaced by one of the connection, and the call
+    /// fails with `CallError::Timeout` after `timeout` milliseconds, or the
+    /// timeout of the client if it is not positive. The response is returned
+    /// whatever its `ret`.
+    pub async fn invoke(&self, mut request: RequestPacket) -> Result<ResponsePacket, CallError> {
+        if request.timeout <= 0 {
+            request.timeout = timeout_millis(self.timeout);
+        }
+        let timeout = Duration::from_millis(request.timeout as u64);
+        request.packet_type = NORMAL;
+
+        let (tx, rx) = oneshot::channel();
+        let id = {
+            let mut pending = self.inner.pending.lock().unwrap();
+            if let Some(e) = pending.closed_error() {
+                return Err(e.into());
+            }
+            // skip ids of calls still waiting once the ids wrap around
+            let id = loop {
+                let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+                if !pending.calls.contains_key(&id) {
+                    break id;
+                }
+            };
+            pending.calls.insert(id, tx);
+            id
+        };
+        request.request_id = id;
+        let _unregister = Unregister {
+            pending: &self.inner.pending,
+            id,
+        };
+
+        let call = async {
+            self.send(request).await?;
+            match rx.await {
+                Ok(response) => Ok(response),
+                Err(_) => Err(self.closed_error().into()),
+            }
+        };
+        match tokio::time::timeout(timeout, call).await {
+            Ok(result) => result,
+            Err(_) => Err(CallError::Timeout),
+        }
+    }
+
+    /// Send `request` as `ONEWAY`, the server does not respond
+    ///
+    /// The `request_id` is replaced by one of the connection.
+    pub async fn invoke_oneway(&self, mut request: RequestPacket) -> Result<(), CallError> {
+        if let Some(e) = self.inner.pending.lock().unwrap().closed_error() {
+            return Err(e.into());
+        }
+        request.packet_type = ONEWAY;
+        request.request_id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        Ok(self.send(request).await?)
+    }
+
+    fn request<Req>(&self, servant: &str, func: &str, req: Req) -> RequestPacket
+    where
+        Req: JcePut,
+    {
+        let mut jce_mut = JceMut::new();
+        req.jce_put(&mut jce_mut, 1);
+        RequestPacket {
+            version: TARS_VERSION,
+            servant_name: servant.to_owned(),
+            func_name: func.to_owned(),
+            buffer: jce_mut.freeze(),
+            timeout: timeout_millis(self.timeout),
+            ..Default::default()
+        }
+    }
+
+    async fn send(&self, request: RequestPacket) -> io::Result<()> {
+        self.inner.writer.lock().await.send(request).await
+    }
+
+    fn closed_error(&self) -> io::Error {
+        self.inner
+            .pending
+            .lock()
+            .unwrap()
+            .closed_error()
+            .unwrap_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed"))
+    }
+}
+
+fn timeout_millis(timeout: Duration) -> i32 {
+    timeout.as_millis().clamp(1, i32::MAX as u128) as i32
+}
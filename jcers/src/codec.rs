@@ -0,0 +1,161 @@
+//! `tokio_util` codec of frames prefixed by their big-endian `u32` length
+//!
+//! By default the frames are those of tars RPC, see [`tars`](crate::tars).
+
+use std::io;
+use std::marker::PhantomData;
+
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::{tars, DecodeOptions, JceError, JceGet, JcePut};
+
+/// Decodes each frame as a `T`, and encodes any `JcePut` as a frame
+///
+/// Errors are `io::Error`s of kind `InvalidData` wrapping the `JceError`.
+///
+/// ## Example
+/// ```rust
+/// use bytes::BytesMut;
+/// use jcers::codec::JceCodec;
+/// use jcers::{JceStruct, JceValue};
+/// use tokio_util::codec::{Decoder, Encoder};
+///
+/// let mut codec = JceCodec::<JceStruct>::new().with_length_includes_header(false);
+/// let mut buf = BytesMut::new();
+/// codec.encode(JceStruct::from([(1, JceValue::I32(5))]), &mut buf).unwrap();
+/// assert_eq!(&buf[..], [0, 0, 0, 2, 0x10, 0x05]);
+/// let value = codec.decode(&mut buf).unwrap().unwrap();
+/// // integers are written in their smallest type
+/// assert_eq!(value, JceStruct::from([(1, JceValue::U8(5))]));
+/// ```
+pub struct JceCodec<T> {
+    max_frame_len: usize,
+    length_includes_header: bool,
+    options: DecodeOptions,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T> JceCodec<T> {
+    /// Codec of tars frames, at most `tars::MAX_FRAME_LEN` long with the
+    /// length counting its own 4 bytes
+    pub fn new() -> Self {
+        JceCodec {
+            max_frame_len: tars::MAX_FRAME_LEN,
+            length_includes_header: true,
+            options: DecodeOptions::default(),
+            _item: PhantomData,
+        }
+    }
+
+    /// Max length of a frame, 4 length bytes included
+    pub fn with_max_frame_len(mut self, max_frame_len: usize) -> Self {
+        self.max_frame_len = max_frame_len;
+        self
+    }
+
+    /// Whether the length counts its own 4 bytes
+    pub fn with_length_includes_header(mut self, length_includes_header: bool) -> Self {
+        self.length_includes_header = length_includes_header;
+        self
+    }
+
+    /// Decode limits of each frame
+    pub fn with_options(mut self, options: DecodeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+
+    pub fn length_includes_header(&self) -> bool {
+        self.length_includes_header
+    }
+
+    /// Length written before a body of `body_len` bytes
+    fn len_field(&self, body_len: usize) -> usize {
+        if self.length_includes_header {
+            body_len + 4
+        } else {
+            body_len
+        }
+    }
+}
+
+impl<T> Default for JceCodec<T> {
+    fn default() -> Self {
+        JceCodec::new()
+    }
+}
+
+impl<T> Clone for JceCodec<T> {
+    fn clone(&self) -> Self {
+        JceCodec {
+            max_frame_len: self.max_frame_len,
+            length_includes_header: self.length_includes_header,
+            options: self.options,
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for JceCodec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JceCodec")
+            .field("max_frame_len", &self.max_frame_len)
+            .field("length_includes_header", &self.length_includes_header)
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
+fn invalid_data(e: JceError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+impl<T> Decoder for JceCodec<T>
+where
+    T: JceGet,
+{
+    type Item = T;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<T>> {
+        let frame_len = tars::frame_len_with(src, self.max_frame_len, self.length_includes_header)
+            .map_err(invalid_data)?;
+        let frame_len = match frame_len {
+            Some(frame_len) => frame_len,
+            None => return Ok(None),
+        };
+        if src.len() < frame_len {
+            src.reserve(frame_len - src.len());
+            return Ok(None);
+        }
+        let mut frame = src.split_to(frame_len).freeze();
+        frame.advance(4);
+        crate::from_buf_with_options(&mut frame, self.options)
+            .map(Some)
+            .map_err(invalid_data)
+    }
+}
+
+impl<T, I> Encoder<I> for JceCodec<T>
+where
+    I: JcePut,
+{
+    type Error = io::Error;
+
+    fn encode(&mut self, item: I, dst: &mut BytesMut) -> io::Result<()> {
+        let body = item.freeze();
+        let len = self.len_field(body.len());
+        if body.len() + 4 > self.max_frame_len || len > u32::MAX as usize {
+            return Err(invalid_data(JceError::FrameLen(len)));
+        }
+        dst.reserve(4 + body.len());
+        dst.put_u32(len as u32);
+        dst.put_slice(&body);
+        Ok(())
+    }
+}
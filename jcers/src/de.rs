@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::hash::Hash;
 
-use super::{JceError, JceHead, JceResult, JceType, JceValue};
+use super::{JceError, JceHead, JcePath, JceResult, JceType};
 use bytes::{Buf, Bytes};
 
 /// Jce Reader
@@ -12,116 +12,712 @@ where
     inner: &'a mut B,
     pub head: JceHead,
     readed: bool,
+    options: DecodeOptions,
+    depth: usize,
+    allocated: Allocated<'a>,
+    start: usize,
+    long_tag: bool,
+    /// fields passed while looking up a later tag, still in the input
+    stash: Vec<Passed>,
+    /// bytes of a contiguous input read past the current position, the
+    /// stashed fields are kept in the input until the reader catches up
+    ahead: usize,
+}
+
+/// A field of a contiguous input, its head and the offset of its value
+#[derive(Debug, Clone, Copy)]
+struct Passed {
+    head: JceHead,
+    long_tag: bool,
+    offset: usize,
+}
+
+/// Limits applied while decoding, guards against hostile payloads
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeOptions {
+    /// max nesting depth of Struct, List, Map and Bytes
+    pub max_depth: usize,
+    /// max element count of a List or Map
+    pub max_collection_len: usize,
+    /// max byte length of a String or Bytes
+    pub max_bytes_len: usize,
+    /// max bytes reserved for collections, strings and bytes in total
+    pub max_total_alloc: usize,
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        DecodeOptions {
+            max_depth: 100,
+            max_collection_len: 1 << 20,
+            max_bytes_len: 1 << 24,
+            max_total_alloc: 1 << 26,
+        }
+    }
+}
+
+/// Bytes allocated so far, shared by a Jce and all its sub Jce
+enum Allocated<'a> {
+    Root(usize),
+    Sub(&'a mut usize),
+}
+
+impl Allocated<'_> {
+    fn get_mut(&mut self) -> &mut usize {
+        match self {
+            Allocated::Root(n) => n,
+            Allocated::Sub(n) => n,
+        }
+    }
 }
 
 /// Deserialize Jce Value
 pub trait JceGet: Sized {
     fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self>;
     fn empty() -> JceResult<Self>;
+    /// Read a `Vec<Self>`, lets `Vec<u8>` read a SimpleList
+    #[doc(hidden)]
+    fn jce_get_vec<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Vec<Self>> {
+        get_list(jce)
+    }
     fn get_from_buf<B: Buf + ?Sized>(buf: &mut B) -> JceResult<Self> {
-        let mut jce = Jce::new(buf);
-        Self::jce_get(&mut jce)
+        let mut jce = Jce::new(buf)?;
+        jce.get()
     }
     fn get_by_tag<B: Buf + ?Sized>(jce: &mut Jce<B>, tag: u8) -> JceResult<Self> {
         jce.get_by_tag(tag)
     }
     fn from_buf<B: Buf + ?Sized>(buf: &mut B) -> JceResult<Self> {
-        let mut jce = Jce::new(buf);
-        Self::jce_get(&mut jce)
+        let mut jce = Jce::new(buf)?;
+        jce.get()
     }
     fn from_boxed_buf<B: Buf + ?Sized>(buf: &mut B) -> JceResult<Self> {
+        if !buf.has_remaining() {
+            return Err(JceError::UnexpectedEof(1, 0));
+        }
         buf.advance(1);
-        let mut jce = Jce::new(buf);
-        Self::jce_get(&mut jce)
+        let mut jce = Jce::new(buf)?;
+        jce.get()
     }
 }
 
+/// Deserialize a value which may borrow from the input slice, see `from_slice`
+///
+/// Implemented for every `JceGet` type, and for `&str` and `&[u8]` living
+/// no longer than `'de`.
+pub trait JceGetRef<'de>: Sized {
+    fn jce_get_ref(jce: &mut Jce<&'de [u8]>) -> JceResult<Self>;
+}
+
+impl<'de, T> JceGetRef<'de> for T
+where
+    T: JceGet,
+{
+    fn jce_get_ref(jce: &mut Jce<&'de [u8]>) -> JceResult<Self> {
+        T::jce_get(jce)
+    }
+}
+
+macro_rules! impl_get {
+    ($fn_name: ident, $output_type: ty) => {
+        /// read value from inner buf, fails if not enough bytes remain
+        pub(crate) fn $fn_name(&mut self) -> JceResult<$output_type> {
+            self.require(std::mem::size_of::<$output_type>())?;
+            Ok(self.inner.$fn_name())
+        }
+    };
+}
+
 impl<'a, B> Jce<'a, B>
 where
     B: Buf + ?Sized,
 {
-    pub fn new(inner: &'a mut B) -> Self {
+    pub fn new(inner: &'a mut B) -> JceResult<Self> {
+        Self::with_options(inner, DecodeOptions::default())
+    }
+
+    pub fn with_options(inner: &'a mut B, options: DecodeOptions) -> JceResult<Self> {
+        let start = inner.remaining();
         let mut jce = Jce {
             inner,
             head: JceHead::default(),
             readed: false,
+            options,
+            depth: 0,
+            allocated: Allocated::Root(0),
+            start,
+            long_tag: false,
+            stash: vec![],
+            ahead: 0,
         };
-        jce.read_head();
-        jce
+        if let Err(e) = jce.read_head() {
+            return Err(e.at(jce.offset()));
+        }
+        Ok(jce)
     }
 
-    pub fn sub_jce<'b>(&'b mut self) -> Jce<'b, B> {
-        Jce::new(self.inner)
+    pub fn sub_jce<'b>(&'b mut self) -> JceResult<Jce<'b, B>> {
+        self.catch_up();
+        let depth = self.depth + 1;
+        if depth > self.options.max_depth {
+            return Err(JceError::DepthLimit(self.options.max_depth).at(self.offset()));
+        }
+        let mut jce = Jce {
+            inner: &mut *self.inner,
+            head: JceHead::default(),
+            readed: false,
+            options: self.options,
+            depth,
+            allocated: Allocated::Sub(self.allocated.get_mut()),
+            start: self.start,
+            long_tag: false,
+            stash: vec![],
+            ahead: 0,
+        };
+        if let Err(e) = jce.read_head() {
+            return Err(e.at(jce.offset()));
+        }
+        Ok(jce)
+    }
+
+    pub fn options(&self) -> &DecodeOptions {
+        &self.options
     }
 
     pub fn has_remaining(&self) -> bool {
-        self.inner.has_remaining()
+        self.inner.remaining() > self.ahead
+    }
+
+    /// Byte offset from where decoding started
+    pub fn offset(&self) -> usize {
+        self.start - self.inner.remaining() + self.ahead
+    }
+
+    /// Ensure at least `len` bytes remain in the inner buf, once it caught
+    /// up with the fields read ahead
+    fn require(&mut self, len: usize) -> JceResult<()> {
+        self.catch_up();
+        let remaining = self.inner.remaining();
+        if remaining < len {
+            Err(JceError::UnexpectedEof(len, remaining))
+        } else {
+            Ok(())
+        }
+    }
+
+    impl_get!(get_u8, u8);
+    impl_get!(get_i16, i16);
+    impl_get!(get_i32, i32);
+    impl_get!(get_i64, i64);
+    impl_get!(get_f32, f32);
+    impl_get!(get_f64, f64);
+
+    pub(crate) fn copy_to_bytes(&mut self, len: usize) -> JceResult<Bytes> {
+        self.require(len)?;
+        Ok(self.inner.copy_to_bytes(len))
+    }
+
+    pub(crate) fn copy_to_slice(&mut self, dst: &mut [u8]) -> JceResult<()> {
+        self.require(dst.len())?;
+        self.inner.copy_to_slice(dst);
+        Ok(())
+    }
+
+    /// Reserve `size` bytes from the total allocation budget
+    fn alloc(&mut self, size: usize) -> JceResult<()> {
+        let max = self.options.max_total_alloc;
+        let allocated = self.allocated.get_mut();
+        match allocated.checked_add(size) {
+            Some(total) if total <= max => {
+                *allocated = total;
+                Ok(())
+            }
+            _ => Err(JceError::AllocLimit(max)),
+        }
+    }
+
+    /// Check a length read from the wire and convert it into usize
+    fn check_len(len: i32) -> JceResult<usize> {
+        usize::try_from(len).map_err(|_| JceError::NegativeLen(len))
+    }
+
+    /// Check a String or Bytes length against limits and reserve it
+    pub(crate) fn bytes_len(&mut self, len: i32) -> JceResult<usize> {
+        let len = self.borrowed_len(len)?;
+        self.alloc(len)?;
+        Ok(len)
+    }
+
+    /// Check a String or Bytes length against limits, without reserving it
+    pub(crate) fn borrowed_len(&mut self, len: i32) -> JceResult<usize> {
+        let len = Self::check_len(len)?;
+        if len > self.options.max_bytes_len {
+            return Err(JceError::BytesLenLimit(len));
+        }
+        self.require(len)?;
+        Ok(len)
+    }
+
+    /// Read the length of a String at the current head
+    pub(crate) fn string_len(&mut self) -> JceResult<i32> {
+        match self.head.ty {
+            JceType::ShortString => Ok(self.get_u8()? as i32),
+            JceType::LongString => self.get_i32(),
+            _ => Err(JceError::ReadLenError(self.head.ty)),
+        }
+    }
+
+    /// Read the length of a SimpleList at the current head, leaves the
+    /// reader at its first byte
+    pub(crate) fn simple_list_len(&mut self) -> JceResult<i32> {
+        self.get_u8()?;
+        self.sub_jce()?.get_by_tag::<i32>(0)
+    }
+
+    /// Read the length of a List or Map, check it against limits and
+    /// reserve `elem_size` bytes per element.
+    ///
+    /// Every element takes at least one byte on the wire, so the returned
+    /// capacity never exceeds the remaining input.
+    pub(crate) fn collection_len(&mut self, elem_size: usize) -> JceResult<(usize, usize)> {
+        let len = self.get_by_tag::<i32>(0)?;
+        self.reserve_collection(len, elem_size)
+    }
+
+    /// Check a List or Map length already read and reserve it, see `collection_len`
+    pub(crate) fn reserve_collection(
+        &mut self,
+        len: i32,
+        elem_size: usize,
+    ) -> JceResult<(usize, usize)> {
+        let len = Self::check_len(len)?;
+        if len > self.options.max_collection_len {
+            return Err(JceError::CollectionLenLimit(len));
+        }
+        let capacity = len.min(self.inner.remaining());
+        self.alloc(capacity.saturating_mul(elem_size))?;
+        Ok((len, capacity))
     }
 
-    pub fn read_head(&mut self) -> JceHead {
-        let byte = self.inner.get_u8();
+    pub fn read_head(&mut self) -> JceResult<JceHead> {
+        let byte = self.get_u8()?;
         let ty = JceType::from(byte & 0xF);
+        if ty == JceType::Unknown {
+            return Err(JceError::UnknownType(byte & 0xF));
+        }
         let mut tag = (byte & 0xF0) >> 4;
-        if tag == 15 {
-            let next_byte = self.inner.get_u8();
-            tag = next_byte & 0xFF;
+        self.long_tag = tag == 15;
+        if self.long_tag {
+            tag = self.get_u8()?;
         }
         let head = JceHead { ty, tag };
         self.head = head;
         self.readed = false;
-        head
+        Ok(head)
+    }
+
+    /// Whether the current head was written in the two byte form
+    pub(crate) fn long_tag(&self) -> bool {
+        self.long_tag
     }
 
     pub fn pass_a_tag(&mut self) -> JceResult<()> {
-        if self.head.ty != JceType::StructEnd {
-            JceValue::jce_get(self).map(|_| ())
-        } else {
-            Ok(())
+        self.skip_value()
+    }
+
+    /// Advance past the value at the current head without decoding it
+    fn skip_value(&mut self) -> JceResult<()> {
+        match self.head.ty {
+            JceType::U8 | JceType::Bool => self.advance(1),
+            JceType::I16 => self.advance(2),
+            JceType::I32 | JceType::F32 => self.advance(4),
+            JceType::I64 | JceType::F64 => self.advance(8),
+            JceType::ShortString => {
+                let len = self.get_u8()?;
+                self.advance(len as usize)
+            }
+            JceType::LongString => {
+                let len = self.get_i32()?;
+                let len = self.borrowed_len(len)?;
+                self.advance(len)
+            }
+            JceType::Map | JceType::List => {
+                let entry = if self.head.ty == JceType::Map { 2 } else { 1 };
+                let mut jce = self.sub_jce()?;
+                let len = jce.get_by_tag::<i32>(0)?;
+                let len = Self::check_len(len)?;
+                if len > jce.options.max_collection_len {
+                    return Err(JceError::CollectionLenLimit(len));
+                }
+                for _ in 0..len * entry {
+                    jce.read_head()?;
+                    jce.skip_value()?;
+                }
+                Ok(())
+            }
+            JceType::Struct => {
+                let mut jce = self.sub_jce()?;
+                while jce.head.ty != JceType::StructEnd {
+                    jce.skip_value()?;
+                    jce.read_head()?;
+                }
+                Ok(())
+            }
+            JceType::Bytes => {
+                let len = self.simple_list_len()?;
+                let len = self.borrowed_len(len)?;
+                self.advance(len)
+            }
+            JceType::StructEnd | JceType::Empty => Ok(()),
+            ty => Err(JceError::UnexpectedType(ty)),
         }
     }
 
+    fn advance(&mut self, len: usize) -> JceResult<()> {
+        self.require(len)?;
+        self.inner.advance(len);
+        Ok(())
+    }
+
+    /// Scan forward to the head with the given tag, stops at the end of
+    /// the struct.
     pub fn go_to_tag(&mut self, tag: u8) -> JceResult<()> {
+        self.catch_up();
+        self.scan_to_tag(tag, None)
+    }
+
+    /// Skip fields up to the head with the given tag, the fields skipped
+    /// are pushed to `passed`
+    fn scan_to_tag(&mut self, tag: u8, mut passed: Option<&mut Vec<Passed>>) -> JceResult<()> {
         if !self.readed {
-            self.pass_a_tag()?;
+            if self.head.ty == JceType::StructEnd {
+                return Err(JceError::TagNotFound(tag));
+            }
+            self.pass_field(&mut passed)?;
         }
-        while self.read_head().tag != tag {
-            self.pass_a_tag()?;
-            if !self.inner.has_remaining() {
+        loop {
+            if !self.has_remaining() {
                 return Err(JceError::TagNotFound(tag));
             }
+            let head = self.read_head()?;
+            if head.ty == JceType::StructEnd {
+                return Err(JceError::TagNotFound(tag));
+            }
+            if head.tag == tag {
+                return Ok(());
+            }
+            self.pass_field(&mut passed)?;
+        }
+    }
+
+    fn pass_field(&mut self, passed: &mut Option<&mut Vec<Passed>>) -> JceResult<()> {
+        if let Some(passed) = passed {
+            passed.push(Passed {
+                head: self.head,
+                long_tag: self.long_tag,
+                offset: self.offset(),
+            });
+        }
+        self.skip_value()
+    }
+
+    fn is_contiguous(&self) -> bool {
+        self.inner.chunk().len() == self.inner.remaining()
+    }
+
+    /// Offset of the input position, before the fields read ahead
+    fn input_offset(&self) -> usize {
+        self.start - self.inner.remaining()
+    }
+
+    /// Advance the input past the fields read ahead, the stashed fields
+    /// are dropped with them
+    fn catch_up(&mut self) {
+        if self.ahead > 0 {
+            self.inner.advance(self.ahead);
+            self.ahead = 0;
+            self.stash.clear();
+        }
+    }
+
+    /// Find the field with the given tag, `None` if it is at the current
+    /// head.
+    ///
+    /// When the input is contiguous, e.g. `Bytes` or `&[u8]`, a later field
+    /// is looked up without advancing the input. The fields passed on the
+    /// way are stashed by offset, and returned here when asked for.
+    fn locate(&mut self, tag: u8) -> JceResult<Option<Passed>> {
+        if let Some(index) = self.stash.iter().position(|f| f.head.tag == tag) {
+            return Ok(Some(self.stash.swap_remove(index)));
+        }
+        if self.ahead == 0 {
+            if self.readed && self.has_remaining() {
+                self.read_head()?;
+            }
+            let here = !self.readed && self.head.tag == tag && self.head.ty != JceType::StructEnd;
+            if here || !self.is_contiguous() {
+                self.seek(tag)?;
+                return Ok(None);
+            }
         }
+        self.find_ahead(tag)?;
+        Ok(Some(Passed {
+            head: self.head,
+            long_tag: self.long_tag,
+            offset: self.offset(),
+        }))
+    }
+
+    /// Scan the contiguous input after the fields read ahead for the head
+    /// with the given tag, and stash the fields passed
+    fn find_ahead(&mut self, tag: u8) -> JceResult<()> {
+        let base = self.input_offset();
+        let chunk = self.inner.chunk();
+        let mut view = &chunk[self.ahead..];
+        let mut jce = Jce {
+            inner: &mut view,
+            head: self.head,
+            readed: self.readed,
+            options: self.options,
+            depth: self.depth,
+            allocated: Allocated::Sub(self.allocated.get_mut()),
+            start: base + chunk.len(),
+            long_tag: self.long_tag,
+            stash: vec![],
+            ahead: 0,
+        };
+        let mut passed = vec![];
+        let found = jce.scan_to_tag(tag, Some(&mut passed));
+        let (head, long_tag, readed, offset) = (jce.head, jce.long_tag, jce.readed, jce.offset());
+        self.head = head;
+        self.long_tag = long_tag;
+        self.readed = readed;
+        self.ahead = offset - base;
+        self.alloc(passed.len() * std::mem::size_of::<Passed>())?;
+        self.stash.extend(passed);
+        found?;
+        self.readed = true;
         Ok(())
     }
 
+    /// Decode a field returned by `locate` out of the contiguous input,
+    /// with the length of its value
+    fn get_at<T>(&mut self, field: Passed) -> JceResult<(T, usize)>
+    where
+        T: JceGet,
+    {
+        let base = self.input_offset();
+        let chunk = self.inner.chunk();
+        let mut view = &chunk[field.offset - base..];
+        let mut jce = Jce {
+            inner: &mut view,
+            head: field.head,
+            readed: true,
+            options: self.options,
+            depth: self.depth,
+            allocated: Allocated::Sub(self.allocated.get_mut()),
+            start: base + chunk.len(),
+            long_tag: field.long_tag,
+            stash: vec![],
+            ahead: 0,
+        };
+        let value = T::jce_get(&mut jce).map_err(|e| e.at(jce.offset()))?;
+        Ok((value, jce.offset() - field.offset))
+    }
+
+    /// Move past a field read by `get_at`, if it was the one found ahead
+    fn consumed(&mut self, field: Passed, len: usize) {
+        if field.offset == self.offset() {
+            self.ahead += len;
+        }
+    }
+
     pub fn get_by_tag<T>(&mut self, tag: u8) -> JceResult<T>
     where
         T: JceGet,
     {
-        if self.head.tag != tag {
+        self.get_in_path(tag, JcePath::Tag(tag))
+    }
+
+    /// Read the value at the current head with `f`, as the derives do for a
+    /// `with` field in `visit_fields`
+    pub fn get_with<T, F>(&mut self, f: F) -> JceResult<T>
+    where
+        F: FnOnce(&mut Self) -> JceResult<T>,
+    {
+        let tag = self.head.tag;
+        self.readed = true;
+        f(self).map_err(|e| e.in_path(JcePath::Tag(tag), self.offset()))
+    }
+
+    /// Same as `get_by_tag`, errors are reported under the given path step
+    pub fn get_in_path<T>(&mut self, tag: u8, step: JcePath) -> JceResult<T>
+    where
+        T: JceGet,
+    {
+        match self.locate(tag) {
+            Ok(None) => T::jce_get(self).map_err(|e| e.in_path(step, self.offset())),
+            Ok(Some(field)) => {
+                let (value, len) = self
+                    .get_at(field)
+                    .map_err(|e| e.in_path(step, field.offset))?;
+                self.consumed(field, len);
+                Ok(value)
+            }
+            Err(e) => Err(e.in_path(step, self.offset())),
+        }
+    }
+
+    /// Move to the head with the given tag and mark its value as readed
+    pub(crate) fn seek(&mut self, tag: u8) -> JceResult<()> {
+        if self.readed || self.head.tag != tag || self.head.ty == JceType::StructEnd {
             self.go_to_tag(tag)?;
         }
         self.readed = true;
-        T::jce_get(self)
+        Ok(())
+    }
+
+    /// Decode a value at the current head, errors get the offset attached
+    pub fn get<T>(&mut self) -> JceResult<T>
+    where
+        T: JceGet,
+    {
+        T::jce_get(self).map_err(|e| e.at(self.offset()))
     }
 
     pub fn end_struct(&mut self) -> JceResult<()> {
-        while self.read_head().ty != JceType::StructEnd {
-            self.pass_a_tag()?;
+        self.catch_up();
+        if self.readed || self.head.ty != JceType::StructEnd {
+            while self.read_head()?.ty != JceType::StructEnd {
+                self.pass_a_tag()?;
+            }
         }
+        self.readed = true;
         Ok(())
     }
+
+    /// Call `f` on each field of the struct at the current head, in wire
+    /// order. A field not read by `f` is skipped.
+    ///
+    /// A boxed struct ends with its StructEnd, an unboxed one with the input.
+    #[doc(hidden)]
+    pub fn visit_fields<F>(&mut self, mut f: F) -> JceResult<()>
+    where
+        F: FnMut(&mut Jce<'_, B>) -> JceResult<()>,
+    {
+        if self.head.ty == JceType::Struct {
+            self.sub_jce()?.visit_body(&mut f)
+        } else {
+            self.visit_body(&mut f)
+        }
+    }
+
+    fn visit_body<F>(&mut self, f: &mut F) -> JceResult<()>
+    where
+        F: FnMut(&mut Jce<'_, B>) -> JceResult<()>,
+    {
+        loop {
+            if self.head.ty == JceType::StructEnd {
+                self.readed = true;
+                return Ok(());
+            }
+            f(self)?;
+            if !self.readed {
+                self.skip_value()?;
+            }
+            if !self.has_remaining() {
+                return Ok(());
+            }
+            self.read_head()?;
+        }
+    }
+
+    /// The error for a struct field missing on the wire, used by derive
+    #[doc(hidden)]
+    pub fn field_not_found(&self, tag: u8) -> JceError {
+        JceError::TagNotFound(tag).in_path(JcePath::Tag(tag), self.offset())
+    }
+}
+
+impl<'a, 'de> Jce<'a, &'de [u8]> {
+    /// Take `len` bytes out of the input without copying
+    pub(crate) fn borrow_bytes(&mut self, len: usize) -> JceResult<&'de [u8]> {
+        self.require(len)?;
+        let (data, rest) = self.inner.split_at(len);
+        *self.inner = rest;
+        Ok(data)
+    }
+
+    /// Same as `get_by_tag`, the value may borrow from the input
+    pub fn get_ref_by_tag<T>(&mut self, tag: u8) -> JceResult<T>
+    where
+        T: JceGetRef<'de>,
+    {
+        self.get_ref_in_path(tag, JcePath::Tag(tag))
+    }
+
+    /// Same as `get_in_path`, the value may borrow from the input
+    pub fn get_ref_in_path<T>(&mut self, tag: u8, step: JcePath) -> JceResult<T>
+    where
+        T: JceGetRef<'de>,
+    {
+        match self.locate(tag) {
+            Ok(None) => T::jce_get_ref(self).map_err(|e| e.in_path(step, self.offset())),
+            Ok(Some(field)) => {
+                let (value, len) = self
+                    .get_ref_at(field)
+                    .map_err(|e| e.in_path(step, field.offset))?;
+                self.consumed(field, len);
+                Ok(value)
+            }
+            Err(e) => Err(e.in_path(step, self.offset())),
+        }
+    }
+
+    /// Same as `get_at`, the value may borrow from the input
+    fn get_ref_at<T>(&mut self, field: Passed) -> JceResult<(T, usize)>
+    where
+        T: JceGetRef<'de>,
+    {
+        let data: &'de [u8] = self.inner;
+        let base = self.input_offset();
+        let mut view = &data[field.offset - base..];
+        let mut jce = Jce {
+            inner: &mut view,
+            head: field.head,
+            readed: true,
+            options: self.options,
+            depth: self.depth,
+            allocated: Allocated::Sub(self.allocated.get_mut()),
+            start: base + data.len(),
+            long_tag: field.long_tag,
+            stash: vec![],
+            ahead: 0,
+        };
+        let value = T::jce_get_ref(&mut jce).map_err(|e| e.at(jce.offset()))?;
+        Ok((value, jce.offset() - field.offset))
+    }
+
+    /// Same as `get`, the value may borrow from the input
+    pub fn get_ref<T>(&mut self) -> JceResult<T>
+    where
+        T: JceGetRef<'de>,
+    {
+        T::jce_get_ref(self).map_err(|e| e.at(self.offset()))
+    }
 }
 
 impl JceGet for bool {
     fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
         match jce.head.ty {
-            JceType::Bool | JceType::U8 => Ok(jce.inner.get_u8() != 0),
+            JceType::Bool | JceType::U8 => Ok(jce.get_u8()? != 0),
+            JceType::Empty => Self::empty(),
             _ => Err(JceError::ReadTypeError(JceType::Bool, jce.head.ty)),
         }
     }
 
     fn empty() -> JceResult<Self> {
-        panic!() //?
+        Ok(false)
     }
 }
 
@@ -130,7 +726,7 @@ impl JceGet for u8 {
     fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
         match jce.head.ty {
             JceType::Empty => Self::empty(),
-            JceType::U8 => Ok(jce.inner.get_u8()),
+            JceType::U8 => jce.get_u8(),
             _ => Err(JceError::ReadTypeError(JceType::U8, jce.head.ty)),
         }
     }
@@ -138,13 +734,28 @@ impl JceGet for u8 {
     fn empty() -> JceResult<Self> {
         Ok(0)
     }
+
+    /// `Vec<u8>` is read from a List, or from the SimpleList of `Bytes` and
+    /// `[u8]`
+    fn jce_get_vec<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Vec<Self>> {
+        if jce.head.ty != JceType::Bytes {
+            return get_list(jce);
+        }
+        let len = jce.simple_list_len()?;
+        let len = jce.bytes_len(len)?;
+        let mut data = vec![0; len];
+        jce.copy_to_slice(&mut data)?;
+        Ok(data)
+    }
 }
 
+// `JcePut` writes the i8 range of an integer as one byte, read back
+// sign-extended
 impl JceGet for i16 {
     fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
         match jce.head.ty {
-            JceType::U8 => u8::jce_get(jce).map(|i| i as i16),
-            JceType::I16 => Ok(jce.inner.get_i16()),
+            JceType::U8 => u8::jce_get(jce).map(|i| i as i8 as i16),
+            JceType::I16 => jce.get_i16(),
             JceType::Empty => Self::empty(),
             _ => Err(JceError::ReadTypeError(JceType::I16, jce.head.ty)),
         }
@@ -158,9 +769,9 @@ impl JceGet for i16 {
 impl JceGet for i32 {
     fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
         match jce.head.ty {
-            JceType::U8 => u8::jce_get(jce).map(|i| i as i32),
+            JceType::U8 => u8::jce_get(jce).map(|i| i as i8 as i32),
             JceType::I16 => i16::jce_get(jce).map(|i| i as i32),
-            JceType::I32 => Ok(jce.inner.get_i32()),
+            JceType::I32 => jce.get_i32(),
             JceType::Empty => Self::empty(),
             _ => Err(JceError::ReadTypeError(JceType::I32, jce.head.ty)),
         }
@@ -174,10 +785,10 @@ impl JceGet for i32 {
 impl JceGet for i64 {
     fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
         match jce.head.ty {
-            JceType::U8 => u8::jce_get(jce).map(|i| i as i64),
+            JceType::U8 => u8::jce_get(jce).map(|i| i as i8 as i64),
             JceType::I16 => i16::jce_get(jce).map(|i| i as i64),
             JceType::I32 => i32::jce_get(jce).map(|i| i as i64),
-            JceType::I64 => Ok(jce.inner.get_i64()),
+            JceType::I64 => jce.get_i64(),
             JceType::Empty => Self::empty(),
             _ => Err(JceError::ReadTypeError(JceType::I64, jce.head.ty)),
         }
@@ -191,7 +802,7 @@ impl JceGet for i64 {
 impl JceGet for f32 {
     fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
         match jce.head.ty {
-            JceType::F32 => Ok(jce.inner.get_f32()),
+            JceType::F32 => jce.get_f32(),
             JceType::Empty => Self::empty(),
             _ => Err(JceError::ReadTypeError(JceType::F32, jce.head.ty)),
         }
@@ -205,7 +816,7 @@ impl JceGet for f32 {
 impl JceGet for f64 {
     fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
         match jce.head.ty {
-            JceType::F64 => Ok(jce.inner.get_f64()),
+            JceType::F64 => jce.get_f64(),
             JceType::Empty => Self::empty(),
             _ => Err(JceError::ReadTypeError(JceType::F64, jce.head.ty)),
         }
@@ -218,18 +829,14 @@ impl JceGet for f64 {
 
 impl JceGet for String {
     fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
-        let len = match jce.head.ty {
-            JceType::ShortString => jce.inner.get_u8() as usize,
-            JceType::LongString => jce.inner.get_i32() as usize,
-            _ => {
-                return Err(JceError::ReadLenError(jce.head.ty));
-            }
-        };
+        let len = jce.string_len()?;
+        let len = jce.bytes_len(len)?;
         if len == 0 {
             Self::empty()
         } else {
-            let data = jce.inner.copy_to_bytes(len);
-            String::from_utf8(data.to_vec()).map_err(|_| JceError::Utf8Error)
+            let mut data = vec![0; len];
+            jce.copy_to_slice(&mut data)?;
+            String::from_utf8(data).map_err(|_| JceError::Utf8Error)
         }
     }
 
@@ -247,13 +854,12 @@ where
         if jce.head.ty != JceType::Map {
             return Err(JceError::ReadTypeError(JceType::Map, jce.head.ty));
         }
-        let mut jce = jce.sub_jce();
-        let len = jce.get_by_tag::<i32>(0)? as usize;
-        let mut map = HashMap::with_capacity(len);
-        for _ in 0..len {
-            let mut jce = jce.sub_jce();
-            let key = jce.get_by_tag(0)?;
-            let value = jce.get_by_tag(1)?;
+        let mut jce = jce.sub_jce()?;
+        let (len, capacity) = jce.collection_len(std::mem::size_of::<(K, V)>())?;
+        let mut map = HashMap::with_capacity(capacity);
+        for i in 0..len {
+            let key = jce.get_in_path(0, JcePath::MapKey(i))?;
+            let value = jce.get_in_path(1, JcePath::MapValue(i))?;
             map.insert(key, value);
         }
         Ok(map)
@@ -269,18 +875,7 @@ where
     V: JceGet,
 {
     fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
-        if jce.head.ty != JceType::List {
-            return Err(JceError::ReadTypeError(JceType::List, jce.head.ty));
-        }
-        let mut jce = jce.sub_jce();
-        let len = jce.get_by_tag::<i32>(0)? as usize;
-        let mut vec = Vec::with_capacity(len);
-        for _ in 0..len {
-            let mut jce = jce.sub_jce();
-            let value = jce.get_by_tag(0)?;
-            vec.push(value);
-        }
-        Ok(vec)
+        V::jce_get_vec(jce)
     }
 
     fn empty() -> JceResult<Self> {
@@ -290,10 +885,14 @@ where
 
 impl JceGet for Bytes {
     fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
-        jce.inner.get_u8();
-        let mut jce = jce.sub_jce();
-        let len = jce.get_by_tag::<i32>(0)? as usize;
-        Ok(jce.inner.copy_to_bytes(len))
+        match jce.head.ty {
+            JceType::Bytes => {}
+            JceType::Empty => return Self::empty(),
+            _ => return Err(JceError::ReadTypeError(JceType::Bytes, jce.head.ty)),
+        }
+        let len = jce.simple_list_len()?;
+        let len = jce.bytes_len(len)?;
+        jce.copy_to_bytes(len)
     }
 
     fn empty() -> JceResult<Self> {
@@ -301,16 +900,64 @@ impl JceGet for Bytes {
     }
 }
 
-// impl JceGet for Vec<u8> { conflicting implementations
-//     fn get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
-//         jce.inner.get_u8();
-//         let len = jce.get_by_tag::<i32>(0)? as usize;
-//         let mut vec = Vec::with_capacity(len);
-//         jce.inner.copy_to_slice(&mut vec)
-//         Ok(vec)
-//     }
-
-//     fn empty() -> JceResult<Self> {
-//         Ok(vec![])
-//     }
-// }
+/// Read a List at the current head
+fn get_list<B, V>(jce: &mut Jce<B>) -> JceResult<Vec<V>>
+where
+    B: Buf + ?Sized,
+    V: JceGet,
+{
+    if jce.head.ty != JceType::List {
+        return Err(JceError::ReadTypeError(JceType::List, jce.head.ty));
+    }
+    let mut jce = jce.sub_jce()?;
+    let (len, capacity) = jce.collection_len(std::mem::size_of::<V>())?;
+    let mut vec = Vec::with_capacity(capacity);
+    for i in 0..len {
+        vec.push(jce.get_in_path(0, JcePath::List(i))?);
+    }
+    Ok(vec)
+}
+
+impl<'de: 'a, 'a> JceGetRef<'de> for &'a str {
+    fn jce_get_ref(jce: &mut Jce<&'de [u8]>) -> JceResult<Self> {
+        let len = jce.string_len()?;
+        let len = jce.borrowed_len(len)?;
+        std::str::from_utf8(jce.borrow_bytes(len)?).map_err(|_| JceError::Utf8Error)
+    }
+}
+
+impl<'de: 'a, 'a> JceGetRef<'de> for &'a [u8] {
+    /// Borrows a SimpleList, a List can not be borrowed
+    fn jce_get_ref(jce: &mut Jce<&'de [u8]>) -> JceResult<Self> {
+        match jce.head.ty {
+            JceType::Bytes => {}
+            JceType::Empty => return Ok(&[]),
+            _ => return Err(JceError::ReadTypeError(JceType::Bytes, jce.head.ty)),
+        }
+        let len = jce.simple_list_len()?;
+        let len = jce.borrowed_len(len)?;
+        jce.borrow_bytes(len)
+    }
+}
+
+macro_rules! impl_get_ref_vec {
+    ($ty: ty) => {
+        impl<'de: 'a, 'a> JceGetRef<'de> for Vec<$ty> {
+            fn jce_get_ref(jce: &mut Jce<&'de [u8]>) -> JceResult<Self> {
+                if jce.head.ty != JceType::List {
+                    return Err(JceError::ReadTypeError(JceType::List, jce.head.ty));
+                }
+                let mut jce = jce.sub_jce()?;
+                let (len, capacity) = jce.collection_len(std::mem::size_of::<$ty>())?;
+                let mut vec = Vec::with_capacity(capacity);
+                for i in 0..len {
+                    vec.push(jce.get_ref_in_path(0, JcePath::List(i))?);
+                }
+                Ok(vec)
+            }
+        }
+    };
+}
+
+impl_get_ref_vec!(&'a str);
+impl_get_ref_vec!(&'a [u8]);
@@ -21,6 +21,137 @@ pub enum JceError {
     ReadError(&'static str),
     /// Jce write error
     WriteError(&'static str),
+    /// Unexpected end of input, (needed bytes, remaining bytes)
+    UnexpectedEof(usize, usize),
+    /// Unknown type code in head, the raw low nibble
+    UnknownType(u8),
+    /// Type can not start a value here
+    UnexpectedType(JceType),
+    /// Type can not be used as a map key
+    UnsupportedMapKey(JceType),
+    /// Negative List, Map, String or Bytes length
+    NegativeLen(i32),
+    /// Nesting deeper than `DecodeOptions::max_depth`
+    DepthLimit(usize),
+    /// List or Map longer than `DecodeOptions::max_collection_len`
+    CollectionLenLimit(usize),
+    /// String or Bytes longer than `DecodeOptions::max_bytes_len`
+    BytesLenLimit(usize),
+    /// Allocation over `DecodeOptions::max_total_alloc`
+    AllocLimit(usize),
+    /// Value is not a variant of the enum, (enum name, value)
+    UnknownEnumValue(&'static str, i32),
+    /// Frame length under the 4 byte prefix or over the max, see `tars`
+    FrameLen(usize),
+    /// Name not in a `tars::UniAttribute`
+    AttributeNotFound(String),
+    /// TUP version other than `tars::TUP_VERSION2` and `tars::TUP_VERSION3`
+    UnsupportedVersion(i16),
+    /// Custom error message, from serde
+    Message(String),
+    /// Error with the position it happened at
+    At {
+        /// byte offset from where decoding started
+        offset: usize,
+        /// tags and containers from the outermost one
+        path: Vec<JcePath>,
+        source: Box<JceError>,
+    },
+}
+
+/// One step on the way to a value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JcePath {
+    /// struct field by tag
+    Tag(u8),
+    /// struct field of a Rust struct, (struct name, field name, tag)
+    Field(&'static str, &'static str, u8),
+    /// list element by index
+    List(usize),
+    /// map key by entry index
+    MapKey(usize),
+    /// map value by entry index
+    MapValue(usize),
+}
+
+impl Display for JcePath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Tag(tag) => write!(f, "{}", tag),
+            Self::Field(ty, field, tag) => write!(f, "{}.{}({})", ty, field, tag),
+            Self::List(index) => write!(f, "list[{}]", index),
+            Self::MapKey(index) => write!(f, "map[{}].key", index),
+            Self::MapValue(index) => write!(f, "map[{}]", index),
+        }
+    }
+}
+
+impl JceError {
+    /// The error without position context
+    pub fn kind(&self) -> &JceError {
+        match self {
+            Self::At { source, .. } => source,
+            e => e,
+        }
+    }
+
+    /// Byte offset the error happened at, if known
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            Self::At { offset, .. } => Some(*offset),
+            _ => None,
+        }
+    }
+
+    /// Path to the value the error happened in, from the outermost one
+    pub fn path(&self) -> &[JcePath] {
+        match self {
+            Self::At { path, .. } => path,
+            _ => &[],
+        }
+    }
+
+    /// Attach the offset, keeps the innermost one if already attached
+    pub(crate) fn at(self, offset: usize) -> Self {
+        match self {
+            e @ Self::At { .. } => e,
+            e => Self::At {
+                offset,
+                path: vec![],
+                source: Box::new(e),
+            },
+        }
+    }
+
+    /// Attach the offset and prepend a step to the path
+    pub(crate) fn in_path(self, step: JcePath, offset: usize) -> Self {
+        match self.at(offset) {
+            Self::At {
+                offset,
+                mut path,
+                source,
+            } => {
+                path.insert(0, step);
+                Self::At {
+                    offset,
+                    path,
+                    source,
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Name the Rust struct field of the outermost tag, used by derive
+    #[doc(hidden)]
+    pub fn in_field(mut self, ty: &'static str, field: &'static str) -> Self {
+        if let Self::At { path, .. } = &mut self {
+            if let Some(JcePath::Tag(tag)) = path.first() {
+                path[0] = JcePath::Field(ty, field, *tag);
+            }
+        }
+        self
+    }
 }
 
 impl Display for JceError {
@@ -41,8 +172,52 @@ impl Display for JceError {
             Self::TagNotFound(tag) => write!(f, "Jce tag not found, tag: {}", tag),
             Self::ReadError(s) => write!(f, "Jce read error: {}", s),
             Self::WriteError(s) => write!(f, "Jce write error: {}", s),
+            Self::UnexpectedEof(need, remaining) => write!(
+                f,
+                "Jce unexpected eof, need {} bytes, remaining {} bytes",
+                need, remaining
+            ),
+            Self::UnknownType(code) => write!(f, "Jce unknown type code: {}", code),
+            Self::UnexpectedType(ty) => write!(f, "Jce unexpected type: {}", ty),
+            Self::UnsupportedMapKey(ty) => {
+                write!(f, "Jce unsupported map key type: {}", ty)
+            }
+            Self::NegativeLen(len) => write!(f, "Jce negative length: {}", len),
+            Self::DepthLimit(max) => write!(f, "Jce nesting depth over limit: {}", max),
+            Self::CollectionLenLimit(len) => {
+                write!(f, "Jce collection length over limit: {}", len)
+            }
+            Self::BytesLenLimit(len) => write!(f, "Jce bytes length over limit: {}", len),
+            Self::AllocLimit(max) => write!(f, "Jce total allocation over limit: {}", max),
+            Self::UnknownEnumValue(ty, value) => {
+                write!(f, "Jce unknown value of enum {}: {}", ty, value)
+            }
+            Self::FrameLen(len) => write!(f, "Jce frame length out of range: {}", len),
+            Self::AttributeNotFound(name) => write!(f, "Jce uni attribute not found: {}", name),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "Jce uni packet version not supported: {}", version)
+            }
+            Self::Message(s) => write!(f, "Jce error: {}", s),
+            Self::At {
+                offset,
+                path,
+                source,
+            } => {
+                write!(f, "{}, at offset {:#x}", source, offset)?;
+                for (i, step) in path.iter().enumerate() {
+                    write!(f, "{}{}", if i == 0 { ", path " } else { " -> " }, step)?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
-impl Error for JceError {}
+impl Error for JceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::At { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
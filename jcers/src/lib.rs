@@ -1,22 +1,50 @@
 #![doc = include_str!("../README.md")]
 
+mod byte_str;
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub mod client;
+#[cfg(feature = "tokio")]
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio")))]
+pub mod codec;
 mod de;
 mod err;
+mod raw;
 mod ser;
+#[cfg(feature = "serde")]
+mod serde_de;
+#[cfg(feature = "serde")]
+mod serde_ser;
+mod sorted_map;
+pub mod tars;
 #[cfg(test)]
 mod test;
+mod type_name;
 mod util;
 mod value;
 
 use std::fmt::LowerHex;
 
+pub use byte_str::ByteStr;
 use bytes::Buf;
-pub use de::{Jce, JceGet};
-pub use err::{JceError, JceResult};
+pub use de::{DecodeOptions, Jce, JceGet, JceGetRef};
+pub use err::{JceError, JcePath, JceResult};
 #[cfg(feature = "derive")]
-#[cfg_attr(docrs, doc(cfg(feature = "derive")))]
-pub use jcers_proc::{JceGet, JcePut};
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
+pub use jcers_proc::{JceGet, JcePut, JceTypeName};
+#[cfg(feature = "idl")]
+#[cfg_attr(docsrs, doc(cfg(feature = "idl")))]
+pub use jcers_proc::{jce_idl, jce_include};
+pub use raw::{JceRawField, JceRawHead, JceRawValue};
 pub use ser::{JceMut, JcePut};
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub use serde_de::from_bytes;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub use serde_ser::to_bytes;
+pub use sorted_map::SortedMap;
+pub use type_name::JceTypeName;
 pub use util::{JceHead, JceType};
 pub use value::*;
 
@@ -25,19 +53,29 @@ pub use value::*;
 /// ## Example
 /// ```rust
 /// use bytes::Bytes;
+/// use jcers::{from_buf, JceValue};
 /// let mut buf = Bytes::from(vec![0x00u8, 0x01u8]);
 /// let v: JceValue = from_buf(&mut buf).unwrap();
-/// assert_eq!(v, JceValue::Byte(0x01u8));
+/// assert_eq!(v, JceValue::U8(0x01u8));
 /// ```
 pub fn from_buf<B, T>(buf: &mut B) -> JceResult<T>
 where
     B: Buf + LowerHex,
     T: JceGet,
 {
-    let mut jce = Jce::new(buf);
-    T::jce_get(&mut jce)
+    let mut jce = Jce::new(buf)?;
+    jce.get()
 }
 
+/// Read data from Buf with custom decode limits
+pub fn from_buf_with_options<B, T>(buf: &mut B, options: DecodeOptions) -> JceResult<T>
+where
+    B: Buf + LowerHex,
+    T: JceGet,
+{
+    let mut jce = Jce::with_options(buf, options)?;
+    jce.get()
+}
 
 /// Read data as a anonymous struct and get the given tag value
 pub fn from_buf_with_tag<B, T>(buf: &mut B, tag: u8) -> JceResult<T>
@@ -45,7 +83,7 @@ where
     B: Buf + LowerHex,
     T: JceGet,
 {
-    let mut jce = Jce::new(buf);
+    let mut jce = Jce::new(buf)?;
     T::get_by_tag(&mut jce, tag)
 }
 
@@ -55,12 +93,29 @@ where
     B: Buf + LowerHex,
     T: JceGet,
 {
-    let mut jce = Jce::new(buf);
-    jce.read_head();
-    let mut jce = jce.sub_jce();
+    let mut jce = Jce::new(buf)?;
+    jce.read_head().map_err(|e| e.at(jce.offset()))?;
+    let mut jce = jce.sub_jce()?;
     T::get_by_tag(&mut jce, tag)
 }
 
+/// Read data from a slice, the value may borrow `&str` and `&[u8]` from it
+///
+/// ## Example
+/// ```rust
+/// let data = [0x06u8, 0x02, b'h', b'i'];
+/// let s: &str = jcers::from_slice(&data).unwrap();
+/// assert_eq!(s, "hi");
+/// ```
+pub fn from_slice<'de, T>(data: &'de [u8]) -> JceResult<T>
+where
+    T: JceGetRef<'de>,
+{
+    let mut buf = data;
+    let mut jce = Jce::new(&mut buf)?;
+    jce.get_ref()
+}
+
 #[test]
 fn test_from_buf() {
     use bytes::Bytes;
@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+
+use bytes::{Buf, BufMut, Bytes};
+
+use super::{
+    Jce, JceError, JceGet, JceMapKey, JceMut, JcePath, JceResult, JceStruct, JceType, JceValue,
+};
+
+/// Head as it was on the wire, the type is given by the value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct JceRawHead {
+    pub tag: u8,
+    /// tag written in the two byte form, even if it is below 15
+    pub long_tag: bool,
+}
+
+/// A value with its head, keeps every encoding choice made on the wire
+/// so that writing it back yields the same bytes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JceRawField {
+    pub head: JceRawHead,
+    pub value: JceRawValue,
+}
+
+/// Value type keeping the wire encoding, see `JceValue` for the normalized one
+#[derive(Debug, Clone, PartialEq)]
+pub enum JceRawValue {
+    U8(u8),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    /// string bytes, not checked for utf8
+    ShortString(Bytes),
+    /// string bytes, not checked for utf8
+    LongString(Bytes),
+    Map {
+        len: Box<JceRawField>,
+        entries: Vec<(JceRawField, JceRawField)>,
+    },
+    List {
+        len: Box<JceRawField>,
+        items: Vec<JceRawField>,
+    },
+    Struct {
+        fields: Vec<JceRawField>,
+        end: JceRawHead,
+    },
+    Empty,
+    Bytes {
+        /// the byte after the head, normally 0x00
+        elem: u8,
+        len: Box<JceRawField>,
+        data: Bytes,
+    },
+}
+
+impl JceRawField {
+    /// Read fields until the buf ends, e.g. an unboxed struct
+    pub fn read_all<B: Buf + ?Sized>(buf: &mut B) -> JceResult<Vec<Self>> {
+        let mut fields = vec![];
+        if !buf.has_remaining() {
+            return Ok(fields);
+        }
+        let mut jce = Jce::new(buf)?;
+        loop {
+            fields.push(jce.get()?);
+            if !jce.has_remaining() {
+                return Ok(fields);
+            }
+            jce.read_head().map_err(|e| e.at(jce.offset()))?;
+        }
+    }
+
+    /// Write fields in order, the reverse of `read_all`
+    pub fn write_all(fields: &[Self], jce_mut: &mut JceMut) -> JceResult<()> {
+        for field in fields {
+            field.write(jce_mut)?;
+        }
+        Ok(())
+    }
+
+    /// Write the field, fails on a string too long for its type
+    ///
+    /// `jce_mut` is left partly written on error.
+    pub fn write(&self, jce_mut: &mut JceMut) -> JceResult<()> {
+        put_raw_head(jce_mut, self.value.ty(), self.head);
+        match &self.value {
+            JceRawValue::U8(v) => jce_mut.0.put_u8(*v),
+            JceRawValue::I16(v) => jce_mut.0.put_i16(*v),
+            JceRawValue::I32(v) => jce_mut.0.put_i32(*v),
+            JceRawValue::I64(v) => jce_mut.0.put_i64(*v),
+            JceRawValue::F32(v) => jce_mut.0.put_f32(*v),
+            JceRawValue::F64(v) => jce_mut.0.put_f64(*v),
+            JceRawValue::ShortString(v) => {
+                let len = u8::try_from(v.len())
+                    .map_err(|_| JceError::WriteError("ShortString over 255 bytes"))?;
+                jce_mut.0.put_u8(len);
+                jce_mut.0.extend_from_slice(v);
+            }
+            JceRawValue::LongString(v) => {
+                let len = i32::try_from(v.len())
+                    .map_err(|_| JceError::WriteError("LongString over i32::MAX bytes"))?;
+                jce_mut.0.put_i32(len);
+                jce_mut.0.extend_from_slice(v);
+            }
+            JceRawValue::Map { len, entries } => {
+                len.write(jce_mut)?;
+                for (k, v) in entries {
+                    k.write(jce_mut)?;
+                    v.write(jce_mut)?;
+                }
+            }
+            JceRawValue::List { len, items } => {
+                len.write(jce_mut)?;
+                for v in items {
+                    v.write(jce_mut)?;
+                }
+            }
+            JceRawValue::Struct { fields, end } => {
+                Self::write_all(fields, jce_mut)?;
+                put_raw_head(jce_mut, 11, *end);
+            }
+            JceRawValue::Empty => {}
+            JceRawValue::Bytes { elem, len, data } => {
+                jce_mut.0.put_u8(*elem);
+                len.write(jce_mut)?;
+                jce_mut.0.extend_from_slice(data);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn freeze(&self) -> JceResult<Bytes> {
+        let mut jce_mut = JceMut::new();
+        self.write(&mut jce_mut)?;
+        Ok(jce_mut.freeze())
+    }
+
+    /// Length of a List, Map or Bytes, read the same way as the typed decoders
+    fn as_len(&self) -> JceResult<i32> {
+        match self.value {
+            JceRawValue::U8(v) => Ok(v as i8 as i32),
+            JceRawValue::I16(v) => Ok(v as i32),
+            JceRawValue::I32(v) => Ok(v),
+            JceRawValue::Empty => Ok(0),
+            _ => Err(JceError::ReadTypeError(JceType::I32, self.value.ty_of())),
+        }
+    }
+}
+
+impl JceGet for JceRawField {
+    fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
+        let head = JceRawHead {
+            tag: jce.head.tag,
+            long_tag: jce.long_tag(),
+        };
+        let value = match jce.head.ty {
+            JceType::U8 | JceType::Bool => JceRawValue::U8(jce.get_u8()?),
+            JceType::I16 => JceRawValue::I16(jce.get_i16()?),
+            JceType::I32 => JceRawValue::I32(jce.get_i32()?),
+            JceType::I64 => JceRawValue::I64(jce.get_i64()?),
+            JceType::F32 => JceRawValue::F32(jce.get_f32()?),
+            JceType::F64 => JceRawValue::F64(jce.get_f64()?),
+            JceType::ShortString => {
+                let len = jce.get_u8()?;
+                let len = jce.bytes_len(len as i32)?;
+                JceRawValue::ShortString(jce.copy_to_bytes(len)?)
+            }
+            JceType::LongString => {
+                let len = jce.get_i32()?;
+                let len = jce.bytes_len(len)?;
+                JceRawValue::LongString(jce.copy_to_bytes(len)?)
+            }
+            JceType::Map => {
+                let mut jce = jce.sub_jce()?;
+                let len = JceRawField::jce_get(&mut jce)?;
+                let elem_size = std::mem::size_of::<(JceRawField, JceRawField)>();
+                let (n, capacity) = jce.reserve_collection(len.as_len()?, elem_size)?;
+                let mut entries = Vec::with_capacity(capacity);
+                for i in 0..n {
+                    jce.read_head()?;
+                    let key = JceRawField::jce_get(&mut jce)
+                        .map_err(|e| e.in_path(JcePath::MapKey(i), jce.offset()))?;
+                    jce.read_head()?;
+                    let value = JceRawField::jce_get(&mut jce)
+                        .map_err(|e| e.in_path(JcePath::MapValue(i), jce.offset()))?;
+                    entries.push((key, value));
+                }
+                JceRawValue::Map {
+                    len: Box::new(len),
+                    entries,
+                }
+            }
+            JceType::List => {
+                let mut jce = jce.sub_jce()?;
+                let len = JceRawField::jce_get(&mut jce)?;
+                let elem_size = std::mem::size_of::<JceRawField>();
+                let (n, capacity) = jce.reserve_collection(len.as_len()?, elem_size)?;
+                let mut items = Vec::with_capacity(capacity);
+                for i in 0..n {
+                    jce.read_head()?;
+                    items.push(
+                        JceRawField::jce_get(&mut jce)
+                            .map_err(|e| e.in_path(JcePath::List(i), jce.offset()))?,
+                    );
+                }
+                JceRawValue::List {
+                    len: Box::new(len),
+                    items,
+                }
+            }
+            JceType::Struct => {
+                let mut jce = jce.sub_jce()?;
+                let mut fields = vec![];
+                while jce.head.ty != JceType::StructEnd {
+                    let tag = jce.head.tag;
+                    fields.push(
+                        JceRawField::jce_get(&mut jce)
+                            .map_err(|e| e.in_path(JcePath::Tag(tag), jce.offset()))?,
+                    );
+                    jce.read_head()?;
+                }
+                let end = JceRawHead {
+                    tag: jce.head.tag,
+                    long_tag: jce.long_tag(),
+                };
+                JceRawValue::Struct { fields, end }
+            }
+            JceType::Empty => JceRawValue::Empty,
+            JceType::Bytes => {
+                let elem = jce.get_u8()?;
+                let mut jce = jce.sub_jce()?;
+                let len = JceRawField::jce_get(&mut jce)?;
+                let n = jce.bytes_len(len.as_len()?)?;
+                JceRawValue::Bytes {
+                    elem,
+                    len: Box::new(len),
+                    data: jce.copy_to_bytes(n)?,
+                }
+            }
+            ty => return Err(JceError::UnexpectedType(ty)),
+        };
+        Ok(JceRawField { head, value })
+    }
+
+    fn empty() -> JceResult<Self> {
+        Ok(JceRawField {
+            head: JceRawHead::default(),
+            value: JceRawValue::Empty,
+        })
+    }
+}
+
+impl JceRawValue {
+    /// type code on the wire
+    fn ty(&self) -> u8 {
+        match self {
+            Self::U8(_) => 0,
+            Self::I16(_) => 1,
+            Self::I32(_) => 2,
+            Self::I64(_) => 3,
+            Self::F32(_) => 4,
+            Self::F64(_) => 5,
+            Self::ShortString(_) => 6,
+            Self::LongString(_) => 7,
+            Self::Map { .. } => 8,
+            Self::List { .. } => 9,
+            Self::Struct { .. } => 10,
+            Self::Empty => 12,
+            Self::Bytes { .. } => 13,
+        }
+    }
+
+    fn ty_of(&self) -> JceType {
+        JceType::from(self.ty())
+    }
+}
+
+fn put_raw_head(jce_mut: &mut JceMut, ty: u8, head: JceRawHead) {
+    if head.long_tag || head.tag >= 15 {
+        jce_mut.0.put_u8(ty | 0xf0);
+        jce_mut.0.put_u8(head.tag);
+    } else {
+        jce_mut.0.put_u8(ty | (head.tag << 4));
+    }
+}
+
+impl TryFrom<JceRawValue> for JceValue {
+    type Error = JceError;
+
+    /// Normalize a raw value, fails on invalid utf8 or unsupported map keys
+    fn try_from(value: JceRawValue) -> JceResult<Self> {
+        Ok(match value {
+            JceRawValue::U8(v) => JceValue::U8(v),
+            JceRawValue::I16(v) => JceValue::I16(v),
+            JceRawValue::I32(v) => JceValue::I32(v),
+            JceRawValue::I64(v) => JceValue::I64(v),
+            JceRawValue::F32(v) => JceValue::F32(v),
+            JceRawValue::F64(v) => JceValue::F64(v),
+            JceRawValue::ShortString(v) | JceRawValue::LongString(v) => {
+                JceValue::String(String::from_utf8(v.to_vec()).map_err(|_| JceError::Utf8Error)?)
+            }
+            JceRawValue::Map { entries, .. } => {
+                let mut map = HashMap::with_capacity(entries.len());
+                for (k, v) in entries {
+                    map.insert(JceMapKey::try_from(k.value)?, JceValue::try_from(v.value)?);
+                }
+                JceValue::Map(map)
+            }
+            JceRawValue::List { items, .. } => JceValue::List(
+                items
+                    .into_iter()
+                    .map(|v| JceValue::try_from(v.value))
+                    .collect::<JceResult<_>>()?,
+            ),
+            JceRawValue::Struct { fields, .. } => {
+                let mut map = JceStruct::new();
+                for field in fields {
+                    map.insert(field.head.tag, JceValue::try_from(field.value)?);
+                }
+                JceValue::Struct(map)
+            }
+            JceRawValue::Empty => JceValue::Empty,
+            JceRawValue::Bytes { data, .. } => JceValue::Bytes(data),
+        })
+    }
+}
+
+impl TryFrom<JceRawValue> for JceMapKey {
+    type Error = JceError;
+
+    fn try_from(value: JceRawValue) -> JceResult<Self> {
+        let ty = value.ty_of();
+        match JceValue::try_from(value)? {
+            JceValue::U8(v) => Ok(JceMapKey::Byte(v)),
+            JceValue::I16(v) => Ok(JceMapKey::I16(v)),
+            JceValue::I32(v) => Ok(JceMapKey::I32(v)),
+            JceValue::I64(v) => Ok(JceMapKey::I64(v)),
+            JceValue::String(v) => Ok(JceMapKey::String(v)),
+            JceValue::Empty => Ok(JceMapKey::Byte(0)),
+            _ => Err(JceError::UnsupportedMapKey(ty)),
+        }
+    }
+}
@@ -3,7 +3,8 @@ use std::collections::HashMap;
 use bytes::{BufMut, Bytes, BytesMut};
 
 /// Jce Writer
-pub struct JceMut(BytesMut);
+#[derive(Default)]
+pub struct JceMut(pub(crate) BytesMut);
 
 macro_rules! impl_put {
     ($fn_name: ident, $input_type: ty) => {
@@ -43,38 +44,60 @@ impl JceMut {
     impl_put!(put_i64, i64);
     impl_put!(put_f32, f32);
     impl_put!(put_f64, f64);
-    impl_put!(put_string, String);
-    impl_put!(put_bytes, Bytes);
+    impl_put!(put_string, &str);
+    impl_put!(put_bytes, &Bytes);
 
     /// put value with the tag
-    pub fn put_map<K: JcePut, V: JcePut>(&mut self, value: HashMap<K, V>, tag: u8) {
+    pub fn put_map<K: JcePut, V: JcePut>(&mut self, value: &HashMap<K, V>, tag: u8) {
         value.jce_put(self, tag)
     }
 
-    /// put value with the tag
-    pub fn put_list<V: JcePut>(&mut self, value: Vec<V>, tag: u8) {
-        value.jce_put(self, tag)
+    /// put value as a List with the tag, `[u8]` included
+    pub fn put_list<V: JcePut>(&mut self, value: &[V], tag: u8) {
+        self.put_head(9, tag);
+        (value.len() as i32).jce_put(self, 0);
+        for v in value {
+            v.jce_put(self, 0);
+        }
+    }
+
+    /// put bytes as a SimpleList with the tag
+    pub(crate) fn put_simple_list(&mut self, value: &[u8], tag: u8) {
+        self.put_head(13, tag);
+        self.put_head(0, 0);
+        (value.len() as i32).jce_put(self, 0);
+        self.0.extend_from_slice(value);
     }
 }
 
 /// Serialize JceValue into Bytes
-pub trait JcePut: Sized {
-    fn jce_put(self, jce_mut: &mut JceMut, tag: u8) {
+///
+/// Values are written by reference, so encoding does not need a clone.
+pub trait JcePut {
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
         jce_mut.put_head(10, tag);
         self.jce_put_raw(jce_mut);
         jce_mut.put_head(11, tag);
     }
-    fn jce_put_raw(self, _: &mut JceMut) {}
-    fn freeze(self) -> Bytes {
+    fn jce_put_raw(&self, _: &mut JceMut) {}
+    fn freeze(&self) -> Bytes {
         let mut jce_mut = JceMut::new();
         self.jce_put_raw(&mut jce_mut);
         jce_mut.freeze()
     }
+    /// Write a `[Self]`, lets `[u8]` be written as a SimpleList
+    #[doc(hidden)]
+    fn jce_put_slice(items: &[Self], jce_mut: &mut JceMut, tag: u8)
+    where
+        Self: Sized,
+    {
+        jce_mut.put_list(items, tag)
+    }
 }
 
 macro_rules! impl_freeze {
     () => {
-        fn freeze(self) -> Bytes {
+        fn freeze(&self) -> Bytes {
             let mut jce_mut = JceMut::new();
             self.jce_put(&mut jce_mut, 0);
             jce_mut.freeze()
@@ -83,21 +106,26 @@ macro_rules! impl_freeze {
 }
 
 impl JcePut for u8 {
-    fn jce_put(self, jce_mut: &mut JceMut, tag: u8) {
-        if self == 0 {
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        if *self == 0 {
             jce_mut.put_head(12, tag);
         } else {
             jce_mut.put_head(0, tag);
-            jce_mut.0.put_u8(self);
+            jce_mut.0.put_u8(*self);
         }
     }
     impl_freeze!();
+
+    /// `[u8]` is written as a SimpleList, as `Bytes`
+    fn jce_put_slice(items: &[Self], jce_mut: &mut JceMut, tag: u8) {
+        jce_mut.put_simple_list(items, tag)
+    }
 }
 
 impl JcePut for bool {
-    fn jce_put(self, jce_mut: &mut JceMut, tag: u8) {
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
         {
-            if self {
+            if *self {
                 1u8
             } else {
                 0u8
@@ -108,54 +136,54 @@ impl JcePut for bool {
 }
 
 impl JcePut for i16 {
-    fn jce_put(self, jce_mut: &mut JceMut, tag: u8) {
-        if self >= i8::MIN as i16 && self <= i8::MAX as i16 {
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        if *self >= i8::MIN as i16 && *self <= i8::MAX as i16 {
             self.to_le_bytes()[0].jce_put(jce_mut, tag);
         } else {
             jce_mut.put_head(1, tag);
-            jce_mut.0.put_i16(self);
+            jce_mut.0.put_i16(*self);
         }
     }
 }
 
 impl JcePut for i32 {
-    fn jce_put(self, jce_mut: &mut JceMut, tag: u8) {
-        if self >= i16::MIN as i32 && self <= i16::MAX as i32 {
-            (self as i16).jce_put(jce_mut, tag);
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        if *self >= i16::MIN as i32 && *self <= i16::MAX as i32 {
+            (*self as i16).jce_put(jce_mut, tag);
         } else {
             jce_mut.put_head(2, tag);
-            jce_mut.0.put_i32(self);
+            jce_mut.0.put_i32(*self);
         }
     }
 }
 
 impl JcePut for i64 {
-    fn jce_put(self, jce_mut: &mut JceMut, tag: u8) {
-        if self >= i32::MIN as i64 && self <= i32::MAX as i64 {
-            (self as i32).jce_put(jce_mut, tag);
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        if *self >= i32::MIN as i64 && *self <= i32::MAX as i64 {
+            (*self as i32).jce_put(jce_mut, tag);
         } else {
             jce_mut.put_head(3, tag);
-            jce_mut.0.put_i64(self);
+            jce_mut.0.put_i64(*self);
         }
     }
 }
 
 impl JcePut for f32 {
-    fn jce_put(self, jce_mut: &mut JceMut, tag: u8) {
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
         jce_mut.put_head(4, tag);
-        jce_mut.0.put_f32(self);
+        jce_mut.0.put_f32(*self);
     }
 }
 
 impl JcePut for f64 {
-    fn jce_put(self, jce_mut: &mut JceMut, tag: u8) {
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
         jce_mut.put_head(5, tag);
-        jce_mut.0.put_f64(self);
+        jce_mut.0.put_f64(*self);
     }
 }
 
-impl JcePut for String {
-    fn jce_put(self, jce_mut: &mut JceMut, tag: u8) {
+impl JcePut for str {
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
         let len = self.len();
         if len < 256 {
             jce_mut.put_head(6, tag);
@@ -169,12 +197,18 @@ impl JcePut for String {
     }
 }
 
+impl JcePut for String {
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        self.as_str().jce_put(jce_mut, tag)
+    }
+}
+
 impl<K, V> JcePut for HashMap<K, V>
 where
     K: JcePut,
     V: JcePut,
 {
-    fn jce_put(self, jce_mut: &mut JceMut, tag: u8) {
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
         jce_mut.put_head(8, tag);
         (self.len() as i32).jce_put(jce_mut, 0);
         for (k, v) in self {
@@ -184,24 +218,44 @@ where
     }
 }
 
+impl<V> JcePut for [V]
+where
+    V: JcePut,
+{
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        V::jce_put_slice(self, jce_mut, tag)
+    }
+}
+
+/// A List, `Vec<u8>` included, as jcers always wrote it
 impl<V> JcePut for Vec<V>
 where
     V: JcePut,
 {
-    fn jce_put(self, jce_mut: &mut JceMut, tag: u8) {
-        jce_mut.put_head(9, tag);
-        (self.len() as i32).jce_put(jce_mut, 0);
-        for v in self {
-            v.jce_put(jce_mut, 0);
-        }
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        jce_mut.put_list(self, tag)
     }
 }
 
 impl JcePut for Bytes {
-    fn jce_put(self, jce_mut: &mut JceMut, tag: u8) {
-        jce_mut.put_head(13, tag);
-        jce_mut.put_head(0, 0);
-        (self.len() as i32).jce_put(jce_mut, 0);
-        jce_mut.0.extend(self);
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        jce_mut.put_simple_list(self, tag)
+    }
+}
+
+impl<T> JcePut for &T
+where
+    T: JcePut + ?Sized,
+{
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        (**self).jce_put(jce_mut, tag)
+    }
+
+    fn jce_put_raw(&self, jce_mut: &mut JceMut) {
+        (**self).jce_put_raw(jce_mut)
+    }
+
+    fn freeze(&self) -> Bytes {
+        (**self).freeze()
     }
 }
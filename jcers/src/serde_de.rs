@@ -0,0 +1,469 @@
+use std::fmt::Display;
+
+use bytes::{Buf, Bytes};
+use serde::de::{self, value::SeqDeserializer, Deserialize, IntoDeserializer, Visitor};
+
+use super::serde_ser::field_tag;
+use super::{Jce, JceError, JceGet, JcePath, JceResult, JceType};
+
+impl de::Error for JceError {
+    fn custom<T: Display>(msg: T) -> Self {
+        JceError::Message(msg.to_string())
+    }
+}
+
+/// Deserialize a value from bytes with serde
+///
+/// Struct fields are matched by tag the same way as `to_bytes` writes them.
+/// A top level struct may be boxed in a Struct head or not, same as derived
+/// `JceGet`.
+///
+/// ## Example
+/// ```rust
+/// #[derive(serde::Deserialize)]
+/// struct Foo {
+///     #[serde(rename = "1")]
+///     a: i32,
+/// }
+/// let foo: Foo = jcers::from_bytes(&[0x10, 0x02]).unwrap();
+/// assert_eq!(foo.a, 2);
+/// ```
+pub fn from_bytes<'de, T: Deserialize<'de>>(mut bytes: &'de [u8]) -> JceResult<T> {
+    let mut jce = Jce::new(&mut bytes)?;
+    T::deserialize(Deserializer {
+        jce: &mut jce,
+        top: true,
+    })
+    .map_err(|e| e.at(jce.offset()))
+}
+
+struct Deserializer<'j, 'a, B>
+where
+    B: Buf + ?Sized,
+{
+    jce: &'j mut Jce<'a, B>,
+    top: bool,
+}
+
+impl<'j, 'a, B> Deserializer<'j, 'a, B>
+where
+    B: Buf + ?Sized,
+{
+    fn new(jce: &'j mut Jce<'a, B>) -> Self {
+        Deserializer { jce, top: false }
+    }
+
+    fn get<T: JceGet>(self) -> JceResult<T> {
+        T::jce_get(self.jce)
+    }
+
+    fn get_int<T, U>(self) -> JceResult<U>
+    where
+        T: JceGet,
+        U: TryFrom<T>,
+    {
+        U::try_from(self.get::<T>()?).map_err(|_| JceError::ReadError("integer out of range"))
+    }
+
+    /// Struct fields by tag, `fields` names them, otherwise tags are the keys
+    fn visit_struct<'de, V: Visitor<'de>>(
+        self,
+        fields: Option<&'static [&'static str]>,
+        visitor: V,
+    ) -> JceResult<V::Value> {
+        match self.jce.head.ty {
+            JceType::Struct => visitor.visit_map(StructAccess {
+                jce: &mut self.jce.sub_jce()?,
+                fields,
+                boxed: true,
+                started: false,
+            }),
+            _ if self.top => visitor.visit_map(StructAccess {
+                jce: self.jce,
+                fields,
+                boxed: false,
+                started: false,
+            }),
+            ty => Err(JceError::ReadTypeError(JceType::Struct, ty)),
+        }
+    }
+}
+
+impl<'de, 'j, 'a, B> de::Deserializer<'de> for Deserializer<'j, 'a, B>
+where
+    B: Buf + ?Sized,
+{
+    type Error = JceError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        match self.jce.head.ty {
+            JceType::Bool | JceType::U8 => visitor.visit_u8(self.get()?),
+            JceType::I16 => visitor.visit_i16(self.get()?),
+            JceType::I32 => visitor.visit_i32(self.get()?),
+            JceType::I64 => visitor.visit_i64(self.get()?),
+            JceType::F32 => visitor.visit_f32(self.get()?),
+            JceType::F64 => visitor.visit_f64(self.get()?),
+            JceType::ShortString | JceType::LongString => visitor.visit_string(self.get()?),
+            JceType::Map => self.deserialize_map(visitor),
+            JceType::List => self.deserialize_seq(visitor),
+            JceType::Struct => self.visit_struct(None, visitor),
+            JceType::Empty => visitor.visit_unit(),
+            JceType::Bytes => visitor.visit_bytes(&self.get::<Bytes>()?),
+            ty => Err(JceError::UnexpectedType(ty)),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_bool(self.get()?)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_i8(self.get_int::<i16, _>()?)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_i16(self.get()?)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_i32(self.get()?)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_i64(self.get()?)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_u8(self.get()?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_u16(self.get_int::<i32, _>()?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_u32(self.get_int::<i64, _>()?)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_u64(self.get_int::<i64, _>()?)
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_f32(self.get()?)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_f64(self.get()?)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        let s: String = self.get()?;
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(JceError::ReadError("expected a single char")),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_string(self.get()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_string(self.get()?)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_bytes(&self.get::<Bytes>()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_bytes(&self.get::<Bytes>()?)
+    }
+
+    /// A present tag is always `Some`, a missing one is left to the struct
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        match self.jce.head.ty {
+            JceType::Empty => visitor.visit_unit(),
+            ty => Err(JceError::ReadTypeError(JceType::Empty, ty)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> JceResult<V::Value> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> JceResult<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        match self.jce.head.ty {
+            JceType::List => {
+                let mut jce = self.jce.sub_jce()?;
+                let (len, _) = jce.collection_len(1)?;
+                let mut access = SeqAccess {
+                    jce: &mut jce,
+                    len,
+                    index: 0,
+                    tagged: false,
+                };
+                let value = visitor.visit_seq(&mut access)?;
+                access.skip_rest()?;
+                Ok(value)
+            }
+            JceType::Bytes => {
+                let bytes: Bytes = self.get()?;
+                visitor.visit_seq(SeqDeserializer::new(bytes.into_iter()))
+            }
+            ty => Err(JceError::ReadTypeError(JceType::List, ty)),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> JceResult<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    /// Tuple struct fields are tagged by position
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> JceResult<V::Value> {
+        match self.jce.head.ty {
+            JceType::Struct => {
+                let value = visitor.visit_seq(SeqAccess {
+                    jce: &mut self.jce.sub_jce()?,
+                    len,
+                    index: 0,
+                    tagged: true,
+                })?;
+                self.jce.end_struct()?;
+                Ok(value)
+            }
+            _ if self.top => visitor.visit_seq(SeqAccess {
+                jce: self.jce,
+                len,
+                index: 0,
+                tagged: true,
+            }),
+            ty => Err(JceError::ReadTypeError(JceType::Struct, ty)),
+        }
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        match self.jce.head.ty {
+            JceType::Map => {
+                let mut jce = self.jce.sub_jce()?;
+                let (len, _) = jce.collection_len(1)?;
+                visitor.visit_map(MapAccess {
+                    jce: &mut jce,
+                    len,
+                    index: 0,
+                })
+            }
+            ty => Err(JceError::ReadTypeError(JceType::Map, ty)),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> JceResult<V::Value> {
+        self.visit_struct(Some(fields), visitor)
+    }
+
+    /// Only unit variants, encoded as the variant index
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> JceResult<V::Value> {
+        let index: u32 = self.get_int::<i64, _>()?;
+        visitor.visit_enum(index.into_deserializer())
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> JceResult<V::Value> {
+        self.jce.pass_a_tag()?;
+        visitor.visit_unit()
+    }
+}
+
+/// Struct fields as a map, keyed by field name or by tag
+struct StructAccess<'j, 'a, B>
+where
+    B: Buf + ?Sized,
+{
+    jce: &'j mut Jce<'a, B>,
+    fields: Option<&'static [&'static str]>,
+    boxed: bool,
+    started: bool,
+}
+
+impl<'de, 'j, 'a, B> de::MapAccess<'de> for StructAccess<'j, 'a, B>
+where
+    B: Buf + ?Sized,
+{
+    type Error = JceError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> JceResult<Option<K::Value>> {
+        if self.started {
+            // an unboxed struct ends with the buf
+            if !self.boxed && !self.jce.has_remaining() {
+                return Ok(None);
+            }
+            self.jce.read_head()?;
+        }
+        self.started = true;
+        if self.jce.head.ty == JceType::StructEnd {
+            return Ok(None);
+        }
+        let tag = self.jce.head.tag;
+        match self.fields {
+            Some(fields) => {
+                let name = fields
+                    .iter()
+                    .enumerate()
+                    .find(|(i, name)| field_tag(*i, name).ok() == Some(tag))
+                    .map(|(_, name)| name.to_string())
+                    .unwrap_or_else(|| tag.to_string());
+                seed.deserialize(name.into_deserializer()).map(Some)
+            }
+            None => seed.deserialize(tag.into_deserializer()).map(Some),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> JceResult<V::Value> {
+        let tag = self.jce.head.tag;
+        seed.deserialize(Deserializer::new(self.jce))
+            .map_err(|e| e.in_path(JcePath::Tag(tag), self.jce.offset()))
+    }
+}
+
+/// List elements, or tuple struct fields tagged by position
+struct SeqAccess<'j, 'a, B>
+where
+    B: Buf + ?Sized,
+{
+    jce: &'j mut Jce<'a, B>,
+    len: usize,
+    index: usize,
+    tagged: bool,
+}
+
+impl<'j, 'a, B> SeqAccess<'j, 'a, B>
+where
+    B: Buf + ?Sized,
+{
+    /// Pass elements the visitor did not ask for
+    fn skip_rest(&mut self) -> JceResult<()> {
+        while self.index < self.len {
+            self.jce.seek(0)?;
+            self.jce.pass_a_tag()?;
+            self.index += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<'de, 'j, 'a, B> de::SeqAccess<'de> for SeqAccess<'j, 'a, B>
+where
+    B: Buf + ?Sized,
+{
+    type Error = JceError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> JceResult<Option<T::Value>> {
+        if self.index >= self.len {
+            return Ok(None);
+        }
+        let index = self.index;
+        self.index += 1;
+        let (tag, step) = if self.tagged {
+            let tag =
+                u8::try_from(index).map_err(|_| JceError::ReadError("field index over 255"))?;
+            (tag, JcePath::Tag(tag))
+        } else {
+            (0, JcePath::List(index))
+        };
+        self.jce
+            .seek(tag)
+            .and_then(|_| seed.deserialize(Deserializer::new(self.jce)))
+            .map(Some)
+            .map_err(|e| e.in_path(step, self.jce.offset()))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+struct MapAccess<'j, 'a, B>
+where
+    B: Buf + ?Sized,
+{
+    jce: &'j mut Jce<'a, B>,
+    len: usize,
+    index: usize,
+}
+
+impl<'de, 'j, 'a, B> de::MapAccess<'de> for MapAccess<'j, 'a, B>
+where
+    B: Buf + ?Sized,
+{
+    type Error = JceError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> JceResult<Option<K::Value>> {
+        if self.index >= self.len {
+            return Ok(None);
+        }
+        let index = self.index;
+        self.jce
+            .seek(0)
+            .and_then(|_| seed.deserialize(Deserializer::new(self.jce)))
+            .map(Some)
+            .map_err(|e| e.in_path(JcePath::MapKey(index), self.jce.offset()))
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> JceResult<V::Value> {
+        let index = self.index;
+        self.index += 1;
+        self.jce
+            .seek(1)
+            .and_then(|_| seed.deserialize(Deserializer::new(self.jce)))
+            .map_err(|e| e.in_path(JcePath::MapValue(index), self.jce.offset()))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
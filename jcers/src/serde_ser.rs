@@ -0,0 +1,414 @@
+use std::fmt::Display;
+
+use bytes::Bytes;
+use serde::ser::{self, Impossible, Serialize};
+
+use super::{JceError, JceMut, JcePut, JceResult};
+
+impl ser::Error for JceError {
+    fn custom<T: Display>(msg: T) -> Self {
+        JceError::Message(msg.to_string())
+    }
+}
+
+/// Serialize a value into Bytes with serde
+///
+/// Structs are written without the outer Struct head, same as
+/// `JcePut::freeze`. A struct field is tagged by its name if the name is a
+/// number (`#[serde(rename = "3")]`), otherwise by its position.
+///
+/// ## Example
+/// ```rust
+/// #[derive(serde::Serialize)]
+/// struct Foo {
+///     #[serde(rename = "1")]
+///     a: i32,
+/// }
+/// let bytes = jcers::to_bytes(&Foo { a: 2 }).unwrap();
+/// assert_eq!(&bytes[..], &[0x10, 0x02]);
+/// ```
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> JceResult<Bytes> {
+    let mut jce_mut = JceMut::new();
+    value.serialize(Serializer {
+        jce_mut: &mut jce_mut,
+        tag: 0,
+        top: true,
+        field: false,
+    })?;
+    Ok(jce_mut.freeze())
+}
+
+/// Tag of a struct field, its name if numeric, otherwise its position
+pub(crate) fn field_tag(index: usize, name: &str) -> JceResult<u8> {
+    match name.parse::<u8>() {
+        Ok(tag) => Ok(tag),
+        Err(_) => u8::try_from(index).map_err(|_| JceError::WriteError("field index over 255")),
+    }
+}
+
+struct Serializer<'a> {
+    jce_mut: &'a mut JceMut,
+    tag: u8,
+    top: bool,
+    /// directly a struct field, the only place a `None` can be left out
+    field: bool,
+}
+
+impl<'a> Serializer<'a> {
+    fn put<T: JcePut + ?Sized>(self, value: &T) -> JceResult<()> {
+        value.jce_put(self.jce_mut, self.tag);
+        Ok(())
+    }
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = ();
+    type Error = JceError;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = SeqSerializer<'a>;
+    type SerializeTupleStruct = StructSerializer<'a>;
+    type SerializeTupleVariant = Impossible<(), JceError>;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeStruct = StructSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), JceError>;
+
+    fn serialize_bool(self, v: bool) -> JceResult<()> {
+        self.put(&v)
+    }
+
+    fn serialize_i8(self, v: i8) -> JceResult<()> {
+        self.put(&(v as i16))
+    }
+
+    fn serialize_i16(self, v: i16) -> JceResult<()> {
+        self.put(&v)
+    }
+
+    fn serialize_i32(self, v: i32) -> JceResult<()> {
+        self.put(&v)
+    }
+
+    fn serialize_i64(self, v: i64) -> JceResult<()> {
+        self.put(&v)
+    }
+
+    fn serialize_u8(self, v: u8) -> JceResult<()> {
+        self.put(&v)
+    }
+
+    fn serialize_u16(self, v: u16) -> JceResult<()> {
+        self.put(&(v as i32))
+    }
+
+    fn serialize_u32(self, v: u32) -> JceResult<()> {
+        self.put(&(v as i64))
+    }
+
+    fn serialize_u64(self, v: u64) -> JceResult<()> {
+        let v = i64::try_from(v).map_err(|_| JceError::WriteError("u64 out of i64 range"))?;
+        self.put(&v)
+    }
+
+    fn serialize_f32(self, v: f32) -> JceResult<()> {
+        self.put(&v)
+    }
+
+    fn serialize_f64(self, v: f64) -> JceResult<()> {
+        self.put(&v)
+    }
+
+    fn serialize_char(self, v: char) -> JceResult<()> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> JceResult<()> {
+        self.put(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> JceResult<()> {
+        self.jce_mut.put_simple_list(v, self.tag);
+        Ok(())
+    }
+
+    /// `None` writes nothing, so a missing tag decodes back into `None`
+    ///
+    /// Elsewhere, as in a list or a map, there is no tag to leave out and
+    /// the length before it would count it, so it is an error.
+    fn serialize_none(self) -> JceResult<()> {
+        match self.field {
+            true => Ok(()),
+            false => Err(JceError::WriteError(
+                "None is only supported as a struct field",
+            )),
+        }
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> JceResult<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> JceResult<()> {
+        self.jce_mut.put_head(12, self.tag);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> JceResult<()> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> JceResult<()> {
+        self.put(&(variant_index as i64))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> JceResult<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> JceResult<()> {
+        Err(JceError::WriteError(
+            "only unit enum variants are supported",
+        ))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> JceResult<SeqSerializer<'a>> {
+        Ok(SeqSerializer::new(self.jce_mut, self.tag, 9, len))
+    }
+
+    fn serialize_tuple(self, len: usize) -> JceResult<SeqSerializer<'a>> {
+        Ok(SeqSerializer::new(self.jce_mut, self.tag, 9, Some(len)))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> JceResult<StructSerializer<'a>> {
+        Ok(StructSerializer::new(self))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> JceResult<Self::SerializeTupleVariant> {
+        Err(JceError::WriteError(
+            "only unit enum variants are supported",
+        ))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> JceResult<MapSerializer<'a>> {
+        Ok(MapSerializer(SeqSerializer::new(
+            self.jce_mut,
+            self.tag,
+            8,
+            len,
+        )))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> JceResult<StructSerializer<'a>> {
+        Ok(StructSerializer::new(self))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> JceResult<Self::SerializeStructVariant> {
+        Err(JceError::WriteError(
+            "only unit enum variants are supported",
+        ))
+    }
+}
+
+/// Writes a List, or a Map when used through `MapSerializer`.
+///
+/// Without a known length the elements go to a temporary buffer first,
+/// since the length is written before them.
+struct SeqSerializer<'a> {
+    jce_mut: &'a mut JceMut,
+    tag: u8,
+    len: usize,
+    buf: Option<JceMut>,
+}
+
+impl<'a> SeqSerializer<'a> {
+    fn new(jce_mut: &'a mut JceMut, tag: u8, ty: u8, len: Option<usize>) -> Self {
+        let buf = match len {
+            Some(len) => {
+                jce_mut.put_head(ty, tag);
+                (len as i32).jce_put(jce_mut, 0);
+                None
+            }
+            None => Some(JceMut::new()),
+        };
+        SeqSerializer {
+            jce_mut,
+            tag,
+            len: 0,
+            buf,
+        }
+    }
+
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T, tag: u8) -> JceResult<()> {
+        let jce_mut = match &mut self.buf {
+            Some(buf) => buf,
+            None => &mut *self.jce_mut,
+        };
+        value.serialize(Serializer {
+            jce_mut,
+            tag,
+            top: false,
+            field: false,
+        })
+    }
+
+    fn finish(self, ty: u8) -> JceResult<()> {
+        if let Some(buf) = self.buf {
+            self.jce_mut.put_head(ty, self.tag);
+            (self.len as i32).jce_put(self.jce_mut, 0);
+            self.jce_mut.0.extend_from_slice(&buf.0);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeSeq for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = JceError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> JceResult<()> {
+        self.len += 1;
+        self.element(value, 0)
+    }
+
+    fn end(self) -> JceResult<()> {
+        self.finish(9)
+    }
+}
+
+impl<'a> ser::SerializeTuple for SeqSerializer<'a> {
+    type Ok = ();
+    type Error = JceError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> JceResult<()> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> JceResult<()> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct MapSerializer<'a>(SeqSerializer<'a>);
+
+impl<'a> ser::SerializeMap for MapSerializer<'a> {
+    type Ok = ();
+    type Error = JceError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> JceResult<()> {
+        self.0.len += 1;
+        self.0.element(key, 0)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> JceResult<()> {
+        self.0.element(value, 1)
+    }
+
+    fn end(self) -> JceResult<()> {
+        self.0.finish(8)
+    }
+}
+
+/// Writes a Struct, or only its fields at the top level
+struct StructSerializer<'a> {
+    jce_mut: &'a mut JceMut,
+    tag: u8,
+    top: bool,
+    index: usize,
+}
+
+impl<'a> StructSerializer<'a> {
+    fn new(ser: Serializer<'a>) -> Self {
+        if !ser.top {
+            ser.jce_mut.put_head(10, ser.tag);
+        }
+        StructSerializer {
+            jce_mut: ser.jce_mut,
+            tag: ser.tag,
+            top: ser.top,
+            index: 0,
+        }
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, name: &str, value: &T) -> JceResult<()> {
+        let tag = field_tag(self.index, name)?;
+        self.index += 1;
+        value.serialize(Serializer {
+            jce_mut: self.jce_mut,
+            tag,
+            top: false,
+            field: true,
+        })
+    }
+
+    fn finish(self) -> JceResult<()> {
+        if !self.top {
+            self.jce_mut.put_head(11, self.tag);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStruct for StructSerializer<'a> {
+    type Ok = ();
+    type Error = JceError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> JceResult<()> {
+        self.field(key, value)
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> JceResult<()> {
+        self.index += 1;
+        Ok(())
+    }
+
+    fn end(self) -> JceResult<()> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for StructSerializer<'a> {
+    type Ok = ();
+    type Error = JceError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> JceResult<()> {
+        self.field("", value)
+    }
+
+    fn end(self) -> JceResult<()> {
+        self.finish()
+    }
+}
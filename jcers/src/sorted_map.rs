@@ -0,0 +1,123 @@
+use std::collections::{btree_map, BTreeMap};
+use std::ops::{Deref, DerefMut};
+
+use bytes::Buf;
+
+use super::{Jce, JceError, JceGet, JceMut, JcePath, JcePut, JceResult, JceType, JceTypeName};
+
+/// Jce map backed by a `BTreeMap`, entries written in key order
+///
+/// A bare `BTreeMap<u8, JceValue>` is the `JceStruct`, so `BTreeMap`s are
+/// wrapped in this to be maps on the wire. Derefs to the inner `BTreeMap`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SortedMap<K, V>(pub BTreeMap<K, V>);
+
+impl<K, V> SortedMap<K, V> {
+    pub fn new() -> Self {
+        SortedMap(BTreeMap::new())
+    }
+
+    pub fn into_inner(self) -> BTreeMap<K, V> {
+        self.0
+    }
+}
+
+impl<K, V> Deref for SortedMap<K, V> {
+    type Target = BTreeMap<K, V>;
+
+    fn deref(&self) -> &BTreeMap<K, V> {
+        &self.0
+    }
+}
+
+impl<K, V> DerefMut for SortedMap<K, V> {
+    fn deref_mut(&mut self) -> &mut BTreeMap<K, V> {
+        &mut self.0
+    }
+}
+
+impl<K, V> From<BTreeMap<K, V>> for SortedMap<K, V> {
+    fn from(map: BTreeMap<K, V>) -> Self {
+        SortedMap(map)
+    }
+}
+
+impl<K: Ord, V, const N: usize> From<[(K, V); N]> for SortedMap<K, V> {
+    fn from(entries: [(K, V); N]) -> Self {
+        SortedMap(BTreeMap::from(entries))
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for SortedMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        SortedMap(BTreeMap::from_iter(iter))
+    }
+}
+
+impl<K, V> IntoIterator for SortedMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = btree_map::IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a SortedMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = btree_map::Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<K, V> JceGet for SortedMap<K, V>
+where
+    K: JceGet + Ord,
+    V: JceGet,
+{
+    fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
+        if jce.head.ty != JceType::Map {
+            return Err(JceError::ReadTypeError(JceType::Map, jce.head.ty));
+        }
+        let mut jce = jce.sub_jce()?;
+        let (len, _) = jce.collection_len(std::mem::size_of::<(K, V)>())?;
+        let mut map = BTreeMap::new();
+        for i in 0..len {
+            let key = jce.get_in_path(0, JcePath::MapKey(i))?;
+            let value = jce.get_in_path(1, JcePath::MapValue(i))?;
+            map.insert(key, value);
+        }
+        Ok(SortedMap(map))
+    }
+
+    fn empty() -> JceResult<Self> {
+        Ok(SortedMap::new())
+    }
+}
+
+impl<K, V> JcePut for SortedMap<K, V>
+where
+    K: JcePut,
+    V: JcePut,
+{
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        jce_mut.put_head(8, tag);
+        (self.len() as i32).jce_put(jce_mut, 0);
+        for (k, v) in self {
+            k.jce_put(jce_mut, 0);
+            v.jce_put(jce_mut, 1);
+        }
+    }
+}
+
+impl<K, V> JceTypeName for SortedMap<K, V>
+where
+    K: JceTypeName,
+    V: JceTypeName,
+{
+    fn type_name() -> String {
+        format!("map<{},{}>", K::type_name(), V::type_name())
+    }
+}
@@ -0,0 +1,410 @@
+//! Tars RPC packets, and the frames they are sent in
+//!
+//! A frame is the big-endian `u32` length of the whole frame, the 4 bytes
+//! included, followed by the packet.
+
+use std::collections::HashMap;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::{Jce, JceError, JceGet, JceMut, JcePut, JceResult, JceTypeName};
+
+/// `iVersion` of tars RPC
+pub const TARS_VERSION: i16 = 1;
+/// `cPacketType` of a call expecting a response
+pub const NORMAL: u8 = 0;
+/// `cPacketType` of a call without response
+pub const ONEWAY: u8 = 1;
+/// `iRet` of a successful call
+pub const SERVER_SUCCESS: i32 = 0;
+/// `iVersion` of a TUP `UniPacket` keeping the type name of each value
+pub const TUP_VERSION2: i16 = 2;
+/// `iVersion` of a TUP `UniPacket` without type names
+pub const TUP_VERSION3: i16 = 3;
+
+/// Max frame length of `decode_frame`, the default of tars servers
+pub const MAX_FRAME_LEN: usize = 10 << 20;
+
+/// A packet struct, fields are read in any order and missing optional fields
+/// are their defaults
+macro_rules! packet {
+    // a missing `require` field is an error
+    (@check require $jce:ident $seen:ident $name:ident $field:ident $tag:literal) => {
+        if $seen & (1 << $tag) == 0 {
+            return Err($jce
+                .field_not_found($tag)
+                .in_field(stringify!($name), stringify!($field)));
+        }
+    };
+    (@check optional $jce:ident $seen:ident $name:ident $field:ident $tag:literal) => {};
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident {
+            $(
+                $(#[$field_meta:meta])*
+                $tag:literal $presence:ident $field:ident: $ty:ty,
+            )*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Default)]
+        pub struct $name {
+            $(
+                $(#[$field_meta])*
+                pub $field: $ty,
+            )*
+        }
+
+        impl JceGet for $name {
+            fn jce_get<B: Buf + ?Sized>(jce: &mut Jce<B>) -> JceResult<Self> {
+                let mut packet = $name::default();
+                // a bit per tag read
+                let mut seen = 0u32;
+                jce.visit_fields(|jce| {
+                    let tag = jce.head.tag;
+                    match tag {
+                        $(
+                            $tag => {
+                                packet.$field = jce
+                                    .get_by_tag($tag)
+                                    .map_err(|e| e.in_field(stringify!($name), stringify!($field)))?;
+                            }
+                        )*
+                        _ => return Ok(()),
+                    }
+                    seen |= 1 << tag;
+                    Ok(())
+                })?;
+                $(
+                    packet!(@check $presence jce seen $name $field $tag);
+                )*
+                Ok(packet)
+            }
+
+            fn empty() -> JceResult<Self> {
+                Ok($name::default())
+            }
+        }
+
+        impl JcePut for $name {
+            fn jce_put_raw(&self, jce_mut: &mut JceMut) {
+                $(
+                    self.$field.jce_put(jce_mut, $tag);
+                )*
+            }
+        }
+    };
+}
+
+packet! {
+    /// `RequestPacket` of tars RPC, a call of `func_name` on `servant_name`
+    pub struct RequestPacket {
+        /// `iVersion`, `TARS_VERSION` for tars RPC
+        1 require version: i16,
+        /// `cPacketType`, `NORMAL` or `ONEWAY`
+        2 optional packet_type: u8,
+        /// `iMessageType`, flags of the call
+        3 optional message_type: i32,
+        /// `iRequestId`, echoed by the response
+        4 require request_id: i32,
+        /// `sServantName`
+        5 require servant_name: String,
+        /// `sFuncName`
+        6 require func_name: String,
+        /// `sBuffer`, the encoded arguments
+        7 require buffer: Bytes,
+        /// `iTimeout` in milliseconds
+        8 optional timeout: i32,
+        /// `context`
+        9 optional context: HashMap<String, String>,
+        /// `status`
+        10 optional status: HashMap<String, String>,
+    }
+}
+
+packet! {
+    /// `ResponsePacket` of tars RPC
+    pub struct ResponsePacket {
+        /// `iVersion`
+        1 require version: i16,
+        /// `cPacketType`
+        2 optional packet_type: u8,
+        /// `iRequestId` of the request
+        3 require request_id: i32,
+        /// `iMessageType`
+        4 optional message_type: i32,
+        /// `iRet`, `SERVER_SUCCESS` or an error code
+        5 optional ret: i32,
+        /// `sBuffer`, the encoded results
+        6 require buffer: Bytes,
+        /// `status`
+        7 optional status: HashMap<String, String>,
+        /// `sResultDesc`, the error message
+        8 optional result_desc: String,
+        /// `context`
+        9 optional context: HashMap<String, String>,
+    }
+}
+
+impl JceTypeName for RequestPacket {
+    fn type_name() -> String {
+        "tars.RequestPacket".to_owned()
+    }
+}
+
+impl JceTypeName for ResponsePacket {
+    fn type_name() -> String {
+        "tars.ResponsePacket".to_owned()
+    }
+}
+
+/// Length of the frame at the start of `buf`, 4 length bytes included, or
+/// `None` if the length itself is incomplete
+pub fn frame_len(buf: &[u8], max_len: usize) -> JceResult<Option<usize>> {
+    frame_len_with(buf, max_len, true)
+}
+
+/// `frame_len` of a length that counts its own 4 bytes or not, the frame
+/// length returned always counts them
+///
+/// An error holds the length as written.
+pub(crate) fn frame_len_with(
+    buf: &[u8],
+    max_len: usize,
+    length_includes_header: bool,
+) -> JceResult<Option<usize>> {
+    let len = match buf.get(..4) {
+        Some(len) => u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize,
+        None => return Ok(None),
+    };
+    let frame_len = match length_includes_header {
+        true => len,
+        false => len.saturating_add(4),
+    };
+    if frame_len < 4 || frame_len > max_len {
+        return Err(JceError::FrameLen(len));
+    }
+    Ok(Some(frame_len))
+}
+
+/// Write `packet` to `dst` as a frame
+pub fn encode_frame<T>(packet: &T, dst: &mut BytesMut)
+where
+    T: JcePut + ?Sized,
+{
+    let body = packet.freeze();
+    dst.reserve(4 + body.len());
+    dst.put_u32((4 + body.len()) as u32);
+    dst.put_slice(&body);
+}
+
+/// Take a frame off `src` and decode its packet, `None` until the frame is
+/// complete
+///
+/// The space the rest of the frame needs is reserved in `src`.
+pub fn decode_frame<T>(src: &mut BytesMut) -> JceResult<Option<T>>
+where
+    T: JceGet,
+{
+    let len = match frame_len(src, MAX_FRAME_LEN)? {
+        Some(len) => len,
+        None => return Ok(None),
+    };
+    if src.len() < len {
+        src.reserve(len - src.len());
+        return Ok(None);
+    }
+    let mut frame = src.split_to(len).freeze();
+    frame.advance(4);
+    crate::from_buf(&mut frame).map(Some)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Data {
+    /// values by name, then by type name
+    V2(HashMap<String, HashMap<String, Bytes>>),
+    V3(HashMap<String, Bytes>),
+}
+
+/// Named values of a TUP packet, each encoded at tag 0
+///
+/// Written as `map<string, map<string, vector<byte>>>` with the type names in
+/// version 2, and as `map<string, vector<byte>>` in version 3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniAttribute {
+    version: i16,
+    data: Data,
+}
+
+impl UniAttribute {
+    /// Empty attribute of `TUP_VERSION2` or `TUP_VERSION3`, other versions
+    /// are an `UnsupportedVersion` error
+    pub fn new(version: i16) -> JceResult<Self> {
+        let data = match version {
+            TUP_VERSION2 => Data::V2(HashMap::new()),
+            TUP_VERSION3 => Data::V3(HashMap::new()),
+            _ => return Err(JceError::UnsupportedVersion(version)),
+        };
+        Ok(UniAttribute { version, data })
+    }
+
+    /// Empty attribute of the same version
+    fn cleared(&self) -> Self {
+        let data = match self.data {
+            Data::V2(_) => Data::V2(HashMap::new()),
+            Data::V3(_) => Data::V3(HashMap::new()),
+        };
+        UniAttribute {
+            version: self.version,
+            data,
+        }
+    }
+
+    pub fn version(&self) -> i16 {
+        self.version
+    }
+
+    /// Set `name` to `value`, replacing the value it had
+    pub fn put<T>(&mut self, name: impl Into<String>, value: &T)
+    where
+        T: JcePut + JceTypeName + ?Sized,
+    {
+        let mut jce_mut = JceMut::new();
+        value.jce_put(&mut jce_mut, 0);
+        let bytes = jce_mut.freeze();
+        match &mut self.data {
+            Data::V2(data) => {
+                data.insert(name.into(), HashMap::from([(T::type_name(), bytes)]));
+            }
+            Data::V3(data) => {
+                data.insert(name.into(), bytes);
+            }
+        }
+    }
+
+    /// Decode the value of `name`
+    ///
+    /// In version 2 the type name is not checked, a value written by other
+    /// implementations may name the same type differently.
+    pub fn get<T>(&self, name: &str) -> JceResult<T>
+    where
+        T: JceGet,
+    {
+        let bytes = match &self.data {
+            Data::V2(data) => data.get(name).and_then(|types| types.values().next()),
+            Data::V3(data) => data.get(name),
+        };
+        match bytes {
+            Some(bytes) => crate::from_buf(&mut bytes.clone()),
+            None => Err(JceError::AttributeNotFound(name.to_owned())),
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        match &self.data {
+            Data::V2(data) => data.contains_key(name),
+            Data::V3(data) => data.contains_key(name),
+        }
+    }
+
+    /// The attribute as the `buffer` of a packet
+    pub fn encode(&self) -> Bytes {
+        let mut jce_mut = JceMut::new();
+        match &self.data {
+            Data::V2(data) => data.jce_put(&mut jce_mut, 0),
+            Data::V3(data) => data.jce_put(&mut jce_mut, 0),
+        }
+        jce_mut.freeze()
+    }
+
+    /// Read the `buffer` of a packet of `version`, 2 or 3
+    pub fn decode(version: i16, mut buffer: Bytes) -> JceResult<Self> {
+        let data = match version {
+            TUP_VERSION2 => Data::V2(crate::from_buf_with_tag(&mut buffer, 0)?),
+            TUP_VERSION3 => Data::V3(crate::from_buf_with_tag(&mut buffer, 0)?),
+            _ => return Err(JceError::UnsupportedVersion(version)),
+        };
+        Ok(UniAttribute { version, data })
+    }
+}
+
+/// A TUP call or response, a `RequestPacket` with a `UniAttribute` as its
+/// `buffer`
+///
+/// Derefs to the attribute for `put` and `get`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniPacket {
+    /// the packet, its `version` and `buffer` come from the attribute
+    pub request: RequestPacket,
+    pub attribute: UniAttribute,
+}
+
+impl UniPacket {
+    /// Empty packet of `TUP_VERSION2` or `TUP_VERSION3`, see
+    /// `UniAttribute::new`
+    pub fn new(version: i16) -> JceResult<Self> {
+        Ok(UniPacket {
+            request: RequestPacket {
+                version,
+                ..Default::default()
+            },
+            attribute: UniAttribute::new(version)?,
+        })
+    }
+
+    /// Read the attribute of a packet
+    pub fn from_request(mut request: RequestPacket) -> JceResult<Self> {
+        let buffer = std::mem::take(&mut request.buffer);
+        let attribute = UniAttribute::decode(request.version, buffer)?;
+        Ok(UniPacket { request, attribute })
+    }
+
+    pub fn to_request(&self) -> RequestPacket {
+        RequestPacket {
+            version: self.attribute.version(),
+            buffer: self.attribute.encode(),
+            ..self.request.clone()
+        }
+    }
+
+    /// Empty packet answering this one, with its names and request id
+    pub fn create_response(&self) -> Self {
+        UniPacket {
+            request: RequestPacket {
+                version: self.attribute.version(),
+                request_id: self.request.request_id,
+                servant_name: self.request.servant_name.clone(),
+                func_name: self.request.func_name.clone(),
+                ..Default::default()
+            },
+            attribute: self.attribute.cleared(),
+        }
+    }
+
+    /// Write the packet to `dst` as a frame
+    pub fn encode(&self, dst: &mut BytesMut) {
+        encode_frame(&self.to_request(), dst)
+    }
+
+    /// Take a frame off `src`, see `decode_frame`
+    pub fn decode(src: &mut BytesMut) -> JceResult<Option<Self>> {
+        match decode_frame(src)? {
+            Some(request) => UniPacket::from_request(request).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+impl std::ops::Deref for UniPacket {
+    type Target = UniAttribute;
+
+    fn deref(&self) -> &Self::Target {
+        &self.attribute
+    }
+}
+
+impl std::ops::DerefMut for UniPacket {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.attribute
+    }
+}
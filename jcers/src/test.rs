@@ -25,7 +25,7 @@ impl JceGet for TestStruct {
 
         let sub = jce.head.ty == JceType::Struct;
         Ok(if sub {
-            let mut sub_jce = jce.sub_jce();
+            let mut sub_jce = jce.sub_jce()?;
             let r = get(&mut sub_jce)?;
             jce.end_struct()?;
             r
@@ -40,7 +40,7 @@ impl JceGet for TestStruct {
 }
 
 impl JcePut for TestStruct {
-    fn jce_put(self, jce_mut: &mut ser::JceMut, tag: u8) {
+    fn jce_put(&self, jce_mut: &mut ser::JceMut, tag: u8) {
         jce_mut.put_head(10, tag);
         self.a.jce_put(jce_mut, 0);
         self.b.jce_put(jce_mut, 1);
@@ -63,3 +63,516 @@ fn test_jce_struct() {
     let t: TestStruct = super::from_buf(&mut bytes).unwrap();
     println!("{:?}", t);
 }
+
+#[test]
+fn test_value_round_trip() {
+    use bytes::Bytes;
+    let data = hex::decode("0a0c1c24123456783604746573744c5c680001000110020b").unwrap();
+    let value: JceValue = super::from_buf(&mut Bytes::from(data.clone())).unwrap();
+    let bytes = value.freeze();
+    assert_eq!(&bytes[..], &data[..]);
+    let t: TestStruct = super::from_buf(&mut bytes.clone()).unwrap();
+    let expected: TestStruct = super::from_buf(&mut Bytes::from(data)).unwrap();
+    assert_eq!(t, expected);
+
+    let data = hex::decode("0c1c24123456783604746573744a0c4b5c68000100011002").unwrap();
+    let value: JceStruct = super::from_buf(&mut Bytes::from(data.clone())).unwrap();
+    assert_eq!(&value.freeze()[..], &data[..]);
+    let bytes = JceValue::Struct(value.clone()).freeze();
+    assert_eq!(
+        super::from_buf::<_, JceStruct>(&mut bytes.clone()),
+        Ok(value)
+    );
+
+    let value = JceValue::List(vec![
+        JceValue::I64(-1 << 40),
+        JceValue::I32(1 << 20),
+        JceValue::String("x".repeat(300)),
+        JceValue::Bytes(Bytes::from_static(b"abc")),
+        JceValue::Map(HashMap::from([(
+            JceMapKey::String("k".to_owned()),
+            JceValue::F64(1.5),
+        )])),
+    ]);
+    let bytes = value.freeze();
+    assert_eq!(
+        super::from_buf::<_, JceValue>(&mut bytes.clone()),
+        Ok(value.clone())
+    );
+    // every element is readable by the typed decoders
+    let list = match value {
+        JceValue::List(list) => list,
+        _ => unreachable!(),
+    };
+    let mut it = list.into_iter().map(|v| v.freeze());
+    assert_eq!(super::from_buf(&mut it.next().unwrap()), Ok(-1i64 << 40));
+    assert_eq!(super::from_buf(&mut it.next().unwrap()), Ok(1i32 << 20));
+    assert_eq!(
+        super::from_buf(&mut it.next().unwrap()),
+        Ok("x".repeat(300))
+    );
+    assert_eq!(
+        super::from_buf(&mut it.next().unwrap()),
+        Ok(Bytes::from_static(b"abc"))
+    );
+    assert_eq!(
+        super::from_buf(&mut it.next().unwrap()),
+        Ok(HashMap::from([("k".to_owned(), 1.5f64)]))
+    );
+}
+
+#[test]
+fn test_put_by_ref() {
+    let mut owned = JceMut::new();
+    let mut borrowed = JceMut::new();
+    "x".repeat(300).jce_put(&mut owned, 1);
+    "x".repeat(300).as_str().jce_put(&mut borrowed, 1);
+    vec!["a".to_owned(), "b".to_owned()].jce_put(&mut owned, 2);
+    ["a", "b"][..].jce_put(&mut borrowed, 2);
+    owned.put_list(&[1i32, 2, 3], 3);
+    borrowed.put_list(&[&1i32, &2, &3], 3);
+    let map = HashMap::from([(1u8, "v".to_owned())]);
+    owned.put_map(&map, 4);
+    borrowed.put_map(&HashMap::from([(&1u8, "v")]), 4);
+    assert_eq!(owned.freeze(), borrowed.freeze());
+}
+
+#[test]
+fn test_byte_lists() {
+    use bytes::Bytes;
+
+    // Vec<u8> stays a List, the slice and Bytes are a SimpleList
+    let mut jce_mut = JceMut::new();
+    vec![1u8, 2].jce_put(&mut jce_mut, 0);
+    assert_eq!(hex::encode(jce_mut.freeze()), "09000200010002");
+    let mut jce_mut = JceMut::new();
+    jce_mut.put_list(&[1u8, 2], 0);
+    assert_eq!(hex::encode(jce_mut.freeze()), "09000200010002");
+    let mut jce_mut = JceMut::new();
+    [1u8, 2][..].jce_put(&mut jce_mut, 0);
+    Bytes::from_static(&[1, 2]).jce_put(&mut jce_mut, 1);
+    let buf = jce_mut.freeze();
+    assert_eq!(hex::encode(&buf), "0d00000201021d0000020102");
+
+    // a Vec<u8> reads both
+    let list = Bytes::from(hex::decode("09000200010002").unwrap());
+    assert_eq!(from_buf::<_, Vec<u8>>(&mut list.clone()), Ok(vec![1, 2]));
+    assert_eq!(
+        from_buf_with_tag::<_, Vec<u8>>(&mut buf.clone(), 1),
+        Ok(vec![1, 2])
+    );
+}
+
+#[test]
+fn test_negative_round_trip() {
+    // the i8 range is written as one byte
+    let mut jce_mut = JceMut::new();
+    (-1i16).jce_put(&mut jce_mut, 0);
+    (-1i32).jce_put(&mut jce_mut, 1);
+    (-1i64).jce_put(&mut jce_mut, 2);
+    (-128i64).jce_put(&mut jce_mut, 3);
+    255u8.jce_put(&mut jce_mut, 4);
+    let buf = jce_mut.freeze();
+    assert_eq!(hex::encode(&buf), "00ff10ff20ff308040ff");
+
+    assert_eq!(from_buf_with_tag::<_, i16>(&mut buf.clone(), 0), Ok(-1));
+    assert_eq!(from_buf_with_tag::<_, i32>(&mut buf.clone(), 1), Ok(-1));
+    assert_eq!(from_buf_with_tag::<_, i64>(&mut buf.clone(), 2), Ok(-1));
+    assert_eq!(from_buf_with_tag::<_, i64>(&mut buf.clone(), 3), Ok(-128));
+    assert_eq!(from_buf_with_tag::<_, u8>(&mut buf.clone(), 4), Ok(255));
+}
+
+#[test]
+fn test_sorted_map() {
+    use std::collections::BTreeMap;
+
+    // a single entry, so the HashMap encodes in the same order
+    let map = SortedMap::from([("k".to_owned(), vec![1i64, 2])]);
+    let mut jce_mut = JceMut::new();
+    map.jce_put(&mut jce_mut, 0);
+    let bytes = jce_mut.freeze();
+    let mut jce_mut = JceMut::new();
+    jce_mut.put_map(&HashMap::from([("k".to_owned(), vec![1i64, 2])]), 0);
+    assert_eq!(bytes, jce_mut.freeze());
+    assert_eq!(from_buf(&mut bytes.clone()), Ok(map));
+    assert_eq!(
+        from_buf::<_, SortedMap<u8, u8>>(&mut bytes.clone()),
+        Err(JceError::ReadTypeError(JceType::U8, JceType::ShortString)
+            .in_path(JcePath::MapKey(0), 4))
+    );
+
+    // a bare BTreeMap<u8, JceValue> is the JceStruct, a struct on the wire
+    let value: BTreeMap<u8, JceValue> =
+        BTreeMap::from([(0, JceValue::U8(1)), (1, JceValue::Empty)]);
+    assert_eq!(hex::encode(value.freeze()), "00011c");
+    assert_eq!(from_buf(&mut value.freeze()), Ok(value.clone()));
+    let e = from_buf::<_, SortedMap<u8, JceValue>>(&mut value.freeze()).unwrap_err();
+    assert_eq!(
+        e.kind(),
+        &JceError::ReadTypeError(JceType::Map, JceType::U8)
+    );
+}
+
+#[test]
+fn test_raw_round_trip() {
+    use bytes::Bytes;
+    let corpus = [
+        "0a0c1c24123456783604746573744c5c680001000110020b",
+        "0c1c24123456783604746573744a0c4b5c68000100011002",
+        // small tag in the long form, 1 sent as i32, explicit zero
+        "f00301000102000000010000",
+        // short string as a long string, tag 20 struct with long end head
+        "170000000161fa14f200000000052cfb00",
+        // map entries out of order, list length as i32
+        "080002060162100106016110021902000000020c0001",
+        "0d000c1d00010003010203",
+    ];
+    for data in corpus {
+        let data = hex::decode(data).unwrap();
+        let fields = JceRawField::read_all(&mut Bytes::from(data.clone())).unwrap();
+        let mut jce_mut = JceMut::new();
+        JceRawField::write_all(&fields, &mut jce_mut).unwrap();
+        assert_eq!(&jce_mut.freeze()[..], &data[..]);
+    }
+
+    let data = hex::decode("f00301000102000000010000").unwrap();
+    let fields = JceRawField::read_all(&mut Bytes::from(data)).unwrap();
+    assert_eq!(
+        fields[0],
+        JceRawField {
+            head: JceRawHead {
+                tag: 3,
+                long_tag: true
+            },
+            value: JceRawValue::U8(1),
+        }
+    );
+    assert_eq!(
+        JceValue::try_from(fields[2].value.clone()),
+        Ok(JceValue::I32(1))
+    );
+
+    // a string too long for its length type is not truncated
+    let field = JceRawField {
+        head: JceRawHead::default(),
+        value: JceRawValue::ShortString(Bytes::from(vec![b'a'; 256])),
+    };
+    assert_eq!(
+        field.freeze(),
+        Err(JceError::WriteError("ShortString over 255 bytes"))
+    );
+}
+
+const TRUNCATE_CORPUS: &[&str] = &[
+    "0a0c1c24123456783604746573744c5c680001000110020b",
+    "0c1c24123456783604746573744a0c4b5c68000100011002",
+    "0d000c1d00010003010203",
+    "1700000003616263f02a0a00010b",
+    "0a1a0b29000200010c0b",
+];
+
+#[test]
+fn test_truncated_input() {
+    use bytes::Bytes;
+    for data in TRUNCATE_CORPUS {
+        let data = hex::decode(data).unwrap();
+        for len in 0..data.len() {
+            let buf = Bytes::copy_from_slice(&data[..len]);
+            let _ = super::from_buf::<_, JceValue>(&mut buf.clone());
+            let _ = super::from_buf::<_, JceStruct>(&mut buf.clone());
+            let _ = super::from_buf::<_, TestStruct>(&mut buf.clone());
+            let _ = super::from_buf::<_, Bytes>(&mut buf.clone());
+            let _ = super::from_buf_with_tag::<_, String>(&mut buf.clone(), 1);
+            let _ = super::from_buf_with_tag_unbox::<_, i64>(&mut buf.clone(), 1);
+            let _ = TestStruct::from_boxed_buf(&mut buf.clone());
+            let _ = JceRawField::read_all(&mut buf.clone());
+        }
+    }
+    let mut empty = Bytes::new();
+    assert_eq!(
+        super::from_buf::<_, JceValue>(&mut empty).map_err(|e| e.kind().clone()),
+        Err(JceError::UnexpectedEof(1, 0))
+    );
+    let mut short = Bytes::from(hex::decode("020001").unwrap());
+    assert_eq!(
+        super::from_buf::<_, i32>(&mut short).map_err(|e| e.kind().clone()),
+        Err(JceError::UnexpectedEof(4, 2))
+    );
+}
+
+#[test]
+fn test_typed_errors() {
+    use bytes::Bytes;
+    let mut buf = Bytes::from(hex::decode("0e00").unwrap());
+    assert_eq!(
+        super::from_buf::<_, JceValue>(&mut buf).map_err(|e| e.kind().clone()),
+        Err(JceError::UnknownType(14))
+    );
+    let mut buf = Bytes::from(hex::decode("0b").unwrap());
+    assert_eq!(
+        super::from_buf::<_, JceValue>(&mut buf).map_err(|e| e.kind().clone()),
+        Err(JceError::UnexpectedType(JceType::StructEnd))
+    );
+    // map { 0: len 1, 0: key float, 1: value 1 }
+    let mut buf = Bytes::from(hex::decode("08000104000000001001").unwrap());
+    assert_eq!(
+        super::from_buf::<_, JceValue>(&mut buf).map_err(|e| e.kind().clone()),
+        Err(JceError::UnsupportedMapKey(JceType::F32))
+    );
+    // map { 0: len 1, 0: key Empty, 1: value 1 }
+    let mut buf = Bytes::from(hex::decode("0800010c1001").unwrap());
+    let v: JceValue = super::from_buf(&mut buf).unwrap();
+    assert_eq!(
+        v,
+        JceValue::Map(HashMap::from([(JceMapKey::Byte(0), JceValue::U8(1))]))
+    );
+    let mut buf = Bytes::from(hex::decode("0c").unwrap());
+    assert_eq!(super::from_buf::<_, bool>(&mut buf), Ok(false));
+}
+
+#[test]
+fn test_garbage_input() {
+    use bytes::Bytes;
+    let mut seed = 0x2545f4914f6cdd1du64;
+    for _ in 0..2000 {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        let len = (seed % 64) as usize;
+        let data: Vec<u8> = (0..len)
+            .map(|i| (seed.rotate_left(i as u32 * 5) & 0xff) as u8)
+            .collect();
+        let buf = Bytes::from(data);
+        let _ = super::from_buf::<_, JceValue>(&mut buf.clone());
+        let _ = super::from_buf::<_, JceStruct>(&mut buf.clone());
+        let _ = super::from_buf::<_, TestStruct>(&mut buf.clone());
+        let _ = JceRawField::read_all(&mut buf.clone());
+    }
+}
+
+#[test]
+fn test_decode_limits() {
+    use bytes::Bytes;
+    // list with length -1
+    let mut buf = Bytes::from(hex::decode("0901ffff").unwrap());
+    assert_eq!(
+        super::from_buf::<_, Vec<u8>>(&mut buf).map_err(|e| e.kind().clone()),
+        Err(JceError::NegativeLen(-1))
+    );
+    // long string with length -2
+    let mut buf = Bytes::from(hex::decode("07fffffffe").unwrap());
+    assert_eq!(
+        super::from_buf::<_, String>(&mut buf).map_err(|e| e.kind().clone()),
+        Err(JceError::NegativeLen(-2))
+    );
+    // list claiming i32::MAX elements
+    let mut buf = Bytes::from(hex::decode("09027fffffff").unwrap());
+    assert_eq!(
+        super::from_buf::<_, Vec<u8>>(&mut buf).map_err(|e| e.kind().clone()),
+        Err(JceError::CollectionLenLimit(i32::MAX as usize))
+    );
+    // nested lists, 200 levels deep
+    let mut data = vec![];
+    for _ in 0..200 {
+        data.extend([0x09, 0x00, 0x01]);
+    }
+    data.push(0x0c);
+    let mut buf = Bytes::from(data);
+    assert_eq!(
+        super::from_buf::<_, JceValue>(&mut buf).map_err(|e| e.kind().clone()),
+        Err(JceError::DepthLimit(100))
+    );
+    // nested structs, 200 levels deep
+    let mut buf = Bytes::from(vec![0x0a; 200]);
+    assert_eq!(
+        super::from_buf::<_, JceValue>(&mut buf).map_err(|e| e.kind().clone()),
+        Err(JceError::DepthLimit(100))
+    );
+    let options = DecodeOptions {
+        max_bytes_len: 2,
+        ..Default::default()
+    };
+    let mut buf = Bytes::from(hex::decode("0603616263").unwrap());
+    assert_eq!(
+        super::from_buf_with_options::<_, String>(&mut buf, options).map_err(|e| e.kind().clone()),
+        Err(JceError::BytesLenLimit(3))
+    );
+    let options = DecodeOptions {
+        max_total_alloc: 4,
+        ..Default::default()
+    };
+    let mut buf = Bytes::from(hex::decode("0d0000060102030405061001").unwrap());
+    assert_eq!(
+        super::from_buf_with_options::<_, JceValue>(&mut buf, options)
+            .map_err(|e| e.kind().clone()),
+        Err(JceError::AllocLimit(4))
+    );
+}
+
+#[test]
+fn test_error_context() {
+    use bytes::Bytes;
+    // struct { 1: list [ "a", map { 0: 0xff } ] }, the map value is a F32 missing bytes
+    let data = "0a19000206016108000100ff14000b";
+    let mut buf = Bytes::from(hex::decode(data).unwrap());
+    let e = super::from_buf::<_, JceValue>(&mut buf).unwrap_err();
+    assert_eq!(e.kind(), &JceError::UnexpectedEof(4, 2));
+    assert_eq!(e.offset(), Some(13));
+    assert_eq!(
+        e.path(),
+        &[JcePath::Tag(1), JcePath::List(1), JcePath::MapValue(0)]
+    );
+    assert_eq!(
+        e.to_string(),
+        "Jce unexpected eof, need 4 bytes, remaining 2 bytes, at offset 0xd, path 1 -> list[1] -> map[0]"
+    );
+}
+
+/// A struct with large fields at tags 0 to 8 and a small one at tag 9
+fn large_struct() -> JceStruct {
+    let item = JceValue::Struct(JceStruct::from([
+        (0, JceValue::String("x".repeat(300))),
+        (1, JceValue::I64(-1 << 40)),
+        (2, JceValue::F32(0.5)),
+    ]));
+    let mut value = JceStruct::new();
+    for tag in 0..9 {
+        value.insert(
+            tag,
+            match tag % 3 {
+                0 => JceValue::List(vec![item.clone(); 100]),
+                1 => JceValue::Map(
+                    (0..100)
+                        .map(|i| (JceMapKey::String(i.to_string()), item.clone()))
+                        .collect(),
+                ),
+                _ => JceValue::Bytes(bytes::Bytes::from(vec![7; 10000])),
+            },
+        );
+    }
+    value.insert(9, JceValue::I32(1234));
+    value
+}
+
+#[test]
+fn test_skip_agrees_with_decode() {
+    let data = large_struct().freeze();
+    let mut corpus: Vec<_> = TRUNCATE_CORPUS
+        .iter()
+        .map(|s| hex::decode(s).unwrap())
+        .collect();
+    corpus.push(data.to_vec());
+    corpus.push(hex::decode("fafff31000000000000000010b").unwrap());
+    for data in corpus {
+        let mut skipped = &data[..];
+        let mut decoded = &data[..];
+        let mut skip = Jce::new(&mut skipped).unwrap();
+        let mut decode = Jce::new(&mut decoded).unwrap();
+        loop {
+            skip.pass_a_tag().unwrap();
+            if decode.head.ty != JceType::StructEnd {
+                JceValue::jce_get(&mut decode).unwrap();
+            }
+            assert_eq!(skip.offset(), decode.offset());
+            if !skip.has_remaining() {
+                break;
+            }
+            assert_eq!(skip.read_head(), decode.read_head());
+        }
+    }
+
+    // the tags before 9 are skipped
+    assert_eq!(from_buf_with_tag::<_, i32>(&mut data.clone(), 9), Ok(1234));
+}
+
+/// Skipping to tag 9 of `large_struct` against decoding the tags before it,
+/// run with `cargo test --release -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn bench_skip_against_decode() {
+    use std::time::Instant;
+
+    let data = large_struct().freeze();
+    let start = Instant::now();
+    for _ in 0..100 {
+        assert_eq!(from_buf_with_tag::<_, i32>(&mut data.clone(), 9), Ok(1234));
+    }
+    let skip_time = start.elapsed();
+    let start = Instant::now();
+    for _ in 0..100 {
+        let mut buf = data.clone();
+        let mut jce = Jce::new(&mut buf).unwrap();
+        while jce.head.tag != 9 {
+            JceValue::jce_get(&mut jce).unwrap();
+            jce.read_head().unwrap();
+        }
+        assert_eq!(jce.get(), Ok(1234));
+    }
+    let decode_time = start.elapsed();
+    println!("skip: {skip_time:?}, decode: {decode_time:?}");
+    assert!(skip_time < decode_time);
+}
+
+#[test]
+fn test_get_by_tag_any_order() {
+    use bytes::{Buf, Bytes};
+    // { 0: 1, 1: "ab", 2: { 0: 3 }, 3: 4 } followed by a tag of the parent
+    let data = hex::decode("0001160261622a00030b30044005").unwrap();
+
+    let mut bytes = Bytes::from(data.clone());
+    let mut jce = Jce::new(&mut bytes).unwrap();
+    assert_eq!(jce.get_by_tag::<i32>(3), Ok(4));
+    assert_eq!(jce.get_by_tag::<String>(1), Ok("ab".to_owned()));
+    assert_eq!(
+        jce.get_by_tag::<JceStruct>(2),
+        Ok(JceStruct::from([(0, JceValue::U8(3))]))
+    );
+    assert_eq!(jce.get_by_tag::<u8>(0), Ok(1));
+    assert_eq!(jce.get_by_tag::<u8>(4), Ok(5));
+
+    let mut slice = &data[..];
+    let mut jce = Jce::new(&mut slice).unwrap();
+    assert_eq!(jce.get_by_tag::<u8>(4), Ok(5));
+    assert_eq!(jce.get_by_tag::<String>(1), Ok("ab".to_owned()));
+
+    // a non contiguous buf only scans forward
+    let mut chain = (&data[..6]).chain(&data[6..]);
+    let mut jce = Jce::new(&mut chain).unwrap();
+    assert_eq!(jce.get_by_tag::<i32>(3), Ok(4));
+    assert_eq!(
+        jce.get_by_tag::<u8>(0).map_err(|e| e.kind().clone()),
+        Err(JceError::TagNotFound(0))
+    );
+
+    // the lookup stops at the end of the struct, tag 1 follows it
+    let mut bytes = Bytes::from(hex::decode("0a00030b1004").unwrap());
+    let mut jce = Jce::new(&mut bytes).unwrap();
+    let mut sub = jce.sub_jce().unwrap();
+    assert_eq!(
+        sub.get_by_tag::<u8>(1).map_err(|e| e.kind().clone()),
+        Err(JceError::TagNotFound(1))
+    );
+    assert_eq!(sub.get_by_tag::<u8>(0), Ok(3));
+    sub.end_struct().unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"\x10\x04"));
+
+    // passed fields stay in the input, only their offsets take from the budget
+    let data = large_struct().freeze();
+    let options = DecodeOptions {
+        max_total_alloc: 1024,
+        ..Default::default()
+    };
+    let mut slice = &data[..];
+    let mut jce = Jce::with_options(&mut slice, options).unwrap();
+    assert_eq!(jce.get_by_tag::<i32>(9), Ok(1234));
+    let options = DecodeOptions {
+        max_total_alloc: 0,
+        ..Default::default()
+    };
+    let mut slice = &data[..];
+    let mut jce = Jce::with_options(&mut slice, options).unwrap();
+    assert_eq!(
+        jce.get_by_tag::<i32>(9).map_err(|e| e.kind().clone()),
+        Err(JceError::AllocLimit(0))
+    );
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+use crate::ByteStr;
+
+/// Tars name of a type, as `int32`, `list<string>` or `map<int64,Foo.Bar>`
+///
+/// Version 2 `UniPacket`s keep it next to each value. Derived with
+/// `#[jce(name = "Module.Struct")]` on a struct, enums are `int32`.
+pub trait JceTypeName {
+    fn type_name() -> String;
+}
+
+macro_rules! impl_type_name {
+    ($ty: ty, $name: expr) => {
+        impl JceTypeName for $ty {
+            fn type_name() -> String {
+                $name.to_owned()
+            }
+        }
+    };
+}
+
+impl_type_name!(bool, "bool");
+impl_type_name!(u8, "char");
+impl_type_name!(i16, "short");
+impl_type_name!(i32, "int32");
+impl_type_name!(i64, "int64");
+impl_type_name!(f32, "float");
+impl_type_name!(f64, "double");
+impl_type_name!(str, "string");
+impl_type_name!(String, "string");
+impl_type_name!(ByteStr, "string");
+impl_type_name!(Bytes, "list<char>");
+
+impl<V> JceTypeName for [V]
+where
+    V: JceTypeName,
+{
+    fn type_name() -> String {
+        format!("list<{}>", V::type_name())
+    }
+}
+
+impl<V> JceTypeName for Vec<V>
+where
+    V: JceTypeName,
+{
+    fn type_name() -> String {
+        <[V]>::type_name()
+    }
+}
+
+impl<K, V> JceTypeName for HashMap<K, V>
+where
+    K: JceTypeName,
+    V: JceTypeName,
+{
+    fn type_name() -> String {
+        format!("map<{},{}>", K::type_name(), V::type_name())
+    }
+}
+
+impl<T> JceTypeName for &T
+where
+    T: JceTypeName + ?Sized,
+{
+    fn type_name() -> String {
+        T::type_name()
+    }
+}
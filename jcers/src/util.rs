@@ -1,5 +1,5 @@
 /// Jce Types
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
 pub enum JceType {
     /// 0
     U8,
@@ -34,6 +34,7 @@ pub enum JceType {
     /// ..
     Unknown,
     /// for Default
+    #[default]
     UnInit,
 }
 
@@ -59,12 +60,6 @@ impl From<u8> for JceType {
     }
 }
 
-impl Default for JceType {
-    fn default() -> Self {
-        JceType::UnInit
-    }
-}
-
 impl std::fmt::Display for JceType {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
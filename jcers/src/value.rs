@@ -1,4 +1,4 @@
-use super::JceType;
+use super::{JceMut, JcePut, JceType};
 use std::collections::{BTreeMap, HashMap};
 
 use bytes::Bytes;
@@ -37,7 +37,7 @@ impl super::JceGet for JceValue {
             JceType::Struct => Ok(Self::Struct(JceStruct::jce_get(jce)?)),
             JceType::Empty => Ok(Self::Empty),
             JceType::Bytes => Ok(Self::Bytes(Bytes::jce_get(jce)?)),
-            _ => panic!("head: {:?}", jce.head), //todo
+            ty => Err(crate::JceError::UnexpectedType(ty)),
         }
     }
 
@@ -46,6 +46,32 @@ impl super::JceGet for JceValue {
     }
 }
 
+impl JcePut for JceValue {
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        match self {
+            Self::Bool(v) => v.jce_put(jce_mut, tag),
+            Self::U8(v) => v.jce_put(jce_mut, tag),
+            Self::I16(v) => v.jce_put(jce_mut, tag),
+            Self::I32(v) => v.jce_put(jce_mut, tag),
+            Self::I64(v) => v.jce_put(jce_mut, tag),
+            Self::F32(v) => v.jce_put(jce_mut, tag),
+            Self::F64(v) => v.jce_put(jce_mut, tag),
+            Self::String(v) => v.jce_put(jce_mut, tag),
+            Self::Map(v) => v.jce_put(jce_mut, tag),
+            Self::List(v) => v.jce_put(jce_mut, tag),
+            Self::Struct(v) => v.jce_put(jce_mut, tag),
+            Self::Empty => jce_mut.put_head(12, tag),
+            Self::Bytes(v) => v.jce_put(jce_mut, tag),
+        }
+    }
+
+    fn freeze(&self) -> Bytes {
+        let mut jce_mut = JceMut::new();
+        self.jce_put(&mut jce_mut, 0);
+        jce_mut.freeze()
+    }
+}
+
 /// Key type for jce map
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum JceMapKey {
@@ -64,12 +90,32 @@ impl super::JceGet for JceMapKey {
             JceType::I32 => Ok(Self::I32(i32::jce_get(jce)?)),
             JceType::I64 => Ok(Self::I64(i64::jce_get(jce)?)),
             JceType::ShortString | JceType::LongString => Ok(Self::String(String::jce_get(jce)?)),
-            _ => panic!(), //todo
+            JceType::Empty => Self::empty(),
+            ty => Err(crate::JceError::UnsupportedMapKey(ty)),
         }
     }
 
+    /// `Empty` on the wire is an integer zero
     fn empty() -> crate::JceResult<Self> {
-        panic!() // todo
+        Ok(Self::Byte(0))
+    }
+}
+
+impl JcePut for JceMapKey {
+    fn jce_put(&self, jce_mut: &mut JceMut, tag: u8) {
+        match self {
+            Self::String(v) => v.jce_put(jce_mut, tag),
+            Self::Byte(v) => v.jce_put(jce_mut, tag),
+            Self::I16(v) => v.jce_put(jce_mut, tag),
+            Self::I32(v) => v.jce_put(jce_mut, tag),
+            Self::I64(v) => v.jce_put(jce_mut, tag),
+        }
+    }
+
+    fn freeze(&self) -> Bytes {
+        let mut jce_mut = JceMut::new();
+        self.jce_put(&mut jce_mut, 0);
+        jce_mut.freeze()
     }
 }
 
@@ -78,20 +124,41 @@ pub type JceStruct = BTreeMap<u8, JceValue>;
 
 impl super::JceGet for JceStruct {
     fn jce_get<B: bytes::Buf + ?Sized>(jce: &mut crate::de::Jce<B>) -> crate::JceResult<Self> {
-        let mut map = BTreeMap::new();
-        while jce.has_remaining() {
-            jce.read_head();
-            let tag = jce.head.tag;
-            if jce.head.ty == JceType::StructEnd {
-                break;
+        fn get<B: bytes::Buf + ?Sized>(
+            jce: &mut crate::de::Jce<B>,
+            boxed: bool,
+        ) -> crate::JceResult<JceStruct> {
+            let mut map = BTreeMap::new();
+            while jce.head.ty != JceType::StructEnd {
+                let tag = jce.head.tag;
+                let value = JceValue::jce_get(jce)
+                    .map_err(|e| e.in_path(crate::JcePath::Tag(tag), jce.offset()))?;
+                map.insert(tag, value);
+                // an unboxed struct ends with the buf
+                if !boxed && !jce.has_remaining() {
+                    break;
+                }
+                jce.read_head()?;
             }
-            let value = JceValue::jce_get(jce)?;
-            map.insert(tag, value);
+            Ok(map)
+        }
+
+        if jce.head.ty == JceType::Struct {
+            get(&mut jce.sub_jce()?, true)
+        } else {
+            get(jce, false)
         }
-        Ok(map)
     }
 
     fn empty() -> crate::JceResult<Self> {
         Ok(BTreeMap::default())
     }
 }
+
+impl JcePut for JceStruct {
+    fn jce_put_raw(&self, jce_mut: &mut JceMut) {
+        for (tag, value) in self {
+            value.jce_put(jce_mut, *tag);
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use bytes::Bytes;
+use jcers::JcePut as _;
+use jcers::{ByteStr, JceError, JcePath};
+use jcers_proc::{JceGet, JcePut};
+
+#[derive(Debug, PartialEq, JceGet, JcePut)]
+pub struct Borrowed<'a> {
+    #[jce(0)]
+    pub name: &'a str,
+    #[jce(1)]
+    pub data: &'a [u8],
+    #[jce(2)]
+    pub id: i32,
+    #[jce(3)]
+    pub inner: Inner<'a>,
+    #[jce(4)]
+    pub list: Vec<&'a str>,
+    #[jce(5)]
+    pub owned: Owned,
+}
+
+#[derive(Debug, PartialEq, JceGet, JcePut)]
+pub struct Inner<'de> {
+    #[jce(0)]
+    pub text: &'de str,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, JceGet, JcePut)]
+pub struct Owned {
+    #[jce(0)]
+    pub text: String,
+    #[jce(1)]
+    pub data: Vec<u8>,
+    #[jce(2)]
+    pub shared: ByteStr,
+}
+
+fn contains(data: &[u8], part: &[u8]) -> bool {
+    data.as_ptr_range().contains(&part.as_ptr())
+}
+
+#[test]
+fn test_borrowed_round_trip() {
+    let data = vec![1, 2, 3];
+    let value = Borrowed {
+        name: "name",
+        data: &data,
+        id: 300,
+        inner: Inner { text: "inner" },
+        list: vec!["a", "b"],
+        owned: Owned {
+            text: "owned".to_owned(),
+            data: vec![4, 5],
+            shared: ByteStr::from_static("shared"),
+        },
+    };
+    let bytes = value.freeze();
+    let decoded: Borrowed = jcers::from_slice(&bytes).unwrap();
+    assert_eq!(decoded, value);
+    assert!(contains(&bytes, decoded.name.as_bytes()));
+    assert!(contains(&bytes, decoded.data));
+    assert!(contains(&bytes, decoded.inner.text.as_bytes()));
+    assert!(contains(&bytes, decoded.list[1].as_bytes()));
+
+    // unknown and out of order tags, { 1: 5, 0: "x" }
+    let data = [0x10, 0x05, 0x06, 0x01, b'x'];
+    assert_eq!(jcers::from_slice::<Inner>(&data), Ok(Inner { text: "x" }));
+
+    // &[u8] is a SimpleList, which a Vec<u8> reads as well
+    let mut buf = bytes.clone();
+    let mut jce = jcers::Jce::new(&mut buf).unwrap();
+    let data: Vec<u8> = jce.get_by_tag(1).unwrap();
+    assert_eq!(data, vec![1, 2, 3]);
+}
+
+#[test]
+fn test_borrowed_any_order() {
+    // { 0: "a", 1: "bc", 2: 5 }
+    let data = [0x06, 0x01, b'a', 0x16, 0x02, b'b', b'c', 0x20, 0x05];
+    let mut slice = &data[..];
+    let mut jce = jcers::Jce::new(&mut slice).unwrap();
+    assert_eq!(jce.get_ref_by_tag::<i32>(2), Ok(5));
+    // the passed fields are borrowed from the input
+    let bc: &str = jce.get_ref_by_tag(1).unwrap();
+    let a: &str = jce.get_ref_by_tag(0).unwrap();
+    assert_eq!((a, bc), ("a", "bc"));
+    assert!(contains(&data, a.as_bytes()));
+    assert!(contains(&data, bc.as_bytes()));
+}
+
+#[test]
+fn test_byte_str_shares_input() {
+    let owned = Owned {
+        text: String::new(),
+        data: vec![],
+        shared: ByteStr::from("shared".to_owned()),
+    };
+    let bytes = owned.freeze();
+    let decoded: Owned = jcers::from_buf(&mut bytes.clone()).unwrap();
+    assert_eq!(decoded.shared, "shared");
+    assert!(contains(&bytes, decoded.shared.as_bytes()));
+
+    assert_eq!(
+        ByteStr::try_from(Bytes::from_static(b"\xff")),
+        Err(JceError::Utf8Error)
+    );
+}
+
+#[test]
+fn test_borrowed_errors() {
+    // tag 0 is a list of u8, it can not be borrowed as &[u8]
+    let e = jcers::from_slice::<&[u8]>(&[0x09, 0x00, 0x01, 0x00, 0x01]).unwrap_err();
+    assert_eq!(
+        e.kind(),
+        &JceError::ReadTypeError(jcers::JceType::Bytes, jcers::JceType::List)
+    );
+    let e = jcers::from_slice::<Inner>(&[0x06, 0x01, 0xff]).unwrap_err();
+    assert_eq!(e.kind(), &JceError::Utf8Error);
+    assert_eq!(e.path(), &[JcePath::Field("Inner", "text", 0)]);
+}
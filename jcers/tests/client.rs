@@ -0,0 +1,220 @@
+#![cfg(feature = "tokio")]
+
+use std::io;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_util::{SinkExt, StreamExt};
+use jcers::client::{CallError, Client};
+use jcers::codec::JceCodec;
+use jcers::tars::{self, RequestPacket, ResponsePacket};
+use jcers::{JceError, JceMut, JcePut};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_util::codec::Framed;
+
+const SERVANT: &str = "Test.EchoServer.EchoObj";
+
+fn response(request: &RequestPacket, ret: i32, value: impl JcePut) -> ResponsePacket {
+    let mut jce_mut = JceMut::new();
+    value.jce_put(&mut jce_mut, 0);
+    ResponsePacket {
+        version: tars::TARS_VERSION,
+        request_id: request.request_id,
+        ret,
+        buffer: jce_mut.freeze(),
+        ..Default::default()
+    }
+}
+
+/// A loopback server of one connection, sending every request it gets to the
+/// returned channel
+///
+/// `echo` answers with the argument at tag 1 of the request, `swap` waits
+/// for a second `swap` and answers both in reverse order, `fail` answers with
+/// an error, and `sleep` and `oneway` are not answered. `close` closes the
+/// connection, and `garbage` answers with a frame that does not decode. The
+/// channel closes once the client closes its side.
+async fn serve() -> (Client, mpsc::UnboundedReceiver<RequestPacket>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut framed = Framed::new(stream, JceCodec::<RequestPacket>::new());
+        let mut swapped = None;
+        while let Some(request) = framed.next().await {
+            let request = request.unwrap();
+            tx.send(request.clone()).unwrap();
+            let arg: String = jcers::from_buf_with_tag(&mut request.buffer.clone(), 1).unwrap();
+            match request.func_name.as_str() {
+                "echo" => framed.send(response(&request, 0, arg)).await.unwrap(),
+                "swap" => match swapped.take() {
+                    None => swapped = Some((request, arg)),
+                    Some((first, first_arg)) => {
+                        framed.send(response(&request, 0, arg)).await.unwrap();
+                        framed.send(response(&first, 0, first_arg)).await.unwrap();
+                    }
+                },
+                "fail" => {
+                    let mut response = response(&request, -3, "");
+                    response.result_desc = "no such function".to_owned();
+                    framed.send(response).await.unwrap();
+                }
+                "close" => return,
+                "garbage" => {
+                    // a valid length, but no required field
+                    let stream = framed.get_mut();
+                    stream.write_all(&[0, 0, 0, 6, 0x10, 0x01]).await.unwrap();
+                }
+                _ => {}
+            }
+        }
+    });
+    let stream = TcpStream::connect(addr).await.unwrap();
+    (Client::new(stream), rx)
+}
+
+#[tokio::test]
+async fn test_call() {
+    let (client, mut requests) = serve().await;
+    let echo: String = client.call(SERVANT, "echo", "hello").await.unwrap();
+    assert_eq!(echo, "hello");
+
+    let request = requests.recv().await.unwrap();
+    assert_eq!(request.version, tars::TARS_VERSION);
+    assert_eq!(request.packet_type, tars::NORMAL);
+    assert_eq!(request.servant_name, SERVANT);
+    assert_eq!(request.func_name, "echo");
+    assert_eq!(request.timeout, 3000);
+    // "hello" at tag 1
+    assert_eq!(request.buffer, Bytes::from_static(b"\x16\x05hello"));
+
+    let e = client
+        .call::<_, String>(SERVANT, "fail", "")
+        .await
+        .unwrap_err();
+    assert!(matches!(e, CallError::Server(-3, ref desc) if desc == "no such function"));
+    // an echo of a string is not an i64
+    let e = client
+        .call::<_, i64>(SERVANT, "echo", "x")
+        .await
+        .unwrap_err();
+    match e {
+        CallError::Jce(e) => assert!(matches!(e.kind(), JceError::ReadTypeError(..))),
+        e => panic!("{}", e),
+    }
+}
+
+#[tokio::test]
+async fn test_multiplexing() {
+    let (client, mut requests) = serve().await;
+    // the second call is answered first
+    let (first, second) = tokio::join!(
+        client.call::<_, String>(SERVANT, "swap", "first"),
+        client.call::<_, String>(SERVANT, "swap", "second"),
+    );
+    assert_eq!(first.unwrap(), "first");
+    assert_eq!(second.unwrap(), "second");
+
+    let first = requests.recv().await.unwrap();
+    let second = requests.recv().await.unwrap();
+    assert_ne!(first.request_id, second.request_id);
+
+    // calls from many tasks share the connection
+    let tasks: Vec<_> = (0..20)
+        .map(|i| {
+            let client = client.clone();
+            tokio::spawn(async move {
+                let echo: String = client.call(SERVANT, "echo", i.to_string()).await.unwrap();
+                assert_eq!(echo, i.to_string());
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn test_timeout() {
+    let (client, mut requests) = serve().await;
+    let client = client.with_timeout(Duration::from_millis(50));
+    let e = client
+        .call::<_, String>(SERVANT, "sleep", "")
+        .await
+        .unwrap_err();
+    assert!(matches!(e, CallError::Timeout));
+    assert_eq!(requests.recv().await.unwrap().timeout, 50);
+
+    // the timeout of a packet is used as is
+    let request = RequestPacket {
+        version: tars::TARS_VERSION,
+        servant_name: SERVANT.to_owned(),
+        func_name: "sleep".to_owned(),
+        buffer: Bytes::from_static(b"\x16\x00"),
+        timeout: 20,
+        ..Default::default()
+    };
+    let e = client.invoke(request).await.unwrap_err();
+    assert!(matches!(e, CallError::Timeout));
+    assert_eq!(requests.recv().await.unwrap().timeout, 20);
+
+    // the connection still works
+    let echo: String = client.call(SERVANT, "echo", "again").await.unwrap();
+    assert_eq!(echo, "again");
+}
+
+#[tokio::test]
+async fn test_oneway() {
+    let (client, mut requests) = serve().await;
+    client.call_oneway(SERVANT, "oneway", "ping").await.unwrap();
+    let request = requests.recv().await.unwrap();
+    assert_eq!(request.packet_type, tars::ONEWAY);
+    assert_eq!(request.func_name, "oneway");
+
+    // a call after it is answered as usual
+    let echo: String = client.call(SERVANT, "echo", "pong").await.unwrap();
+    assert_eq!(echo, "pong");
+}
+
+#[tokio::test]
+async fn test_closed() {
+    let (client, mut requests) = serve().await;
+    let sleep = tokio::spawn({
+        let client = client.clone();
+        async move { client.call::<_, String>(SERVANT, "sleep", "").await }
+    });
+    requests.recv().await.unwrap();
+    let close = client.call::<_, String>(SERVANT, "close", "").await;
+    let sleep = sleep.await.unwrap();
+    // waiting calls fail once the server closes the connection
+    for e in [sleep.unwrap_err(), close.unwrap_err()] {
+        assert!(matches!(e, CallError::Io(ref e) if e.kind() == io::ErrorKind::UnexpectedEof));
+    }
+    let e = client
+        .call::<_, String>(SERVANT, "echo", "")
+        .await
+        .unwrap_err();
+    assert!(matches!(e, CallError::Io(_)));
+}
+
+#[tokio::test]
+async fn test_bad_frame() {
+    let (client, mut requests) = serve().await;
+    let e = client
+        .call::<_, String>(SERVANT, "garbage", "")
+        .await
+        .unwrap_err();
+    assert!(matches!(e, CallError::Io(ref e) if e.kind() == io::ErrorKind::InvalidData));
+    assert_eq!(requests.recv().await.unwrap().func_name, "garbage");
+    // the client shuts its side down, the server does not wait on it
+    let end = tokio::time::timeout(Duration::from_secs(5), requests.recv()).await;
+    assert!(matches!(end, Ok(None)));
+    let e = client
+        .call::<_, String>(SERVANT, "echo", "")
+        .await
+        .unwrap_err();
+    assert!(matches!(e, CallError::Io(ref e) if e.kind() == io::ErrorKind::InvalidData));
+}
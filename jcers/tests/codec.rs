@@ -0,0 +1,123 @@
+#![cfg(feature = "tokio")]
+
+use std::io;
+
+use bytes::{Bytes, BytesMut};
+use futures_util::{SinkExt, StreamExt};
+use jcers::codec::JceCodec;
+use jcers::tars::{self, RequestPacket};
+use jcers::{JceError, JceValue};
+use tokio::io::AsyncWriteExt;
+use tokio_util::codec::{Decoder, Encoder, FramedRead, FramedWrite};
+
+fn request(request_id: i32) -> RequestPacket {
+    RequestPacket {
+        version: tars::TARS_VERSION,
+        request_id,
+        servant_name: "Test.HelloServer.HelloObj".to_owned(),
+        func_name: "hello".to_owned(),
+        buffer: Bytes::from_static(&[0x16, 0x02, b'h', b'i']),
+        timeout: 3000,
+        ..Default::default()
+    }
+}
+
+fn jce_error(e: &io::Error) -> &JceError {
+    assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+    e.get_ref().unwrap().downcast_ref().unwrap()
+}
+
+#[tokio::test]
+async fn test_duplex() {
+    // a small buffer so frames are split across reads
+    let (client, server) = tokio::io::duplex(16);
+    let mut sink = FramedWrite::new(client, JceCodec::<RequestPacket>::new());
+    let mut stream = FramedRead::new(server, JceCodec::<RequestPacket>::new());
+
+    let write = async move {
+        for id in 1..=3 {
+            sink.send(request(id)).await.unwrap();
+        }
+    };
+    let read = async move {
+        let mut packets = vec![];
+        while let Some(packet) = stream.next().await {
+            packets.push(packet.unwrap());
+        }
+        packets
+    };
+    let ((), packets) = tokio::join!(write, read);
+    assert_eq!(packets, [request(1), request(2), request(3)]);
+}
+
+#[tokio::test]
+async fn test_duplex_values() {
+    let (mut client, server) = tokio::io::duplex(64);
+    let codec = JceCodec::<JceValue>::new().with_length_includes_header(false);
+    let mut stream = FramedRead::new(server, codec);
+
+    // "hi" at tag 0, then 300 at tag 0
+    client
+        .write_all(&[
+            0, 0, 0, 4, 0x06, 0x02, b'h', b'i', 0, 0, 0, 3, 0x01, 0x01, 0x2c,
+        ])
+        .await
+        .unwrap();
+    drop(client);
+    let mut values = vec![];
+    while let Some(value) = stream.next().await {
+        values.push(value.unwrap());
+    }
+    assert_eq!(
+        values,
+        [JceValue::String("hi".to_owned()), JceValue::I16(300)]
+    );
+}
+
+#[test]
+fn test_frame_len() {
+    let mut codec = JceCodec::<RequestPacket>::new().with_max_frame_len(64);
+    assert_eq!(codec.max_frame_len(), 64);
+    assert!(codec.length_includes_header());
+
+    // partial frames take the space they need
+    let mut dst = BytesMut::new();
+    codec.encode(&request(1), &mut dst).unwrap();
+    let frame = dst.split().freeze();
+    let mut src = BytesMut::from(&frame[..10]);
+    assert!(codec.decode(&mut src).unwrap().is_none());
+    assert!(src.capacity() >= frame.len());
+    src.extend_from_slice(&frame[10..]);
+    assert_eq!(codec.decode(&mut src).unwrap(), Some(request(1)));
+    assert!(src.is_empty());
+
+    let mut src = BytesMut::from(&[0, 0, 0, 65][..]);
+    let e = codec.decode(&mut src).unwrap_err();
+    assert_eq!(jce_error(&e), &JceError::FrameLen(65));
+    let mut src = BytesMut::from(&[0, 0, 0, 3][..]);
+    let e = codec.decode(&mut src).unwrap_err();
+    assert_eq!(jce_error(&e), &JceError::FrameLen(3));
+
+    // without the header, the length is checked with its 4 bytes added
+    let mut body_codec = codec.clone().with_length_includes_header(false);
+    let mut src = BytesMut::from(&[0, 0, 0, 60][..]);
+    assert!(body_codec.decode(&mut src).unwrap().is_none());
+    assert!(src.capacity() >= 64);
+    let mut src = BytesMut::from(&[0, 0, 0, 61][..]);
+    let e = body_codec.decode(&mut src).unwrap_err();
+    assert_eq!(jce_error(&e), &JceError::FrameLen(61));
+    let mut src = BytesMut::from(&[0xff, 0xff, 0xff, 0xff][..]);
+    let e = body_codec.decode(&mut src).unwrap_err();
+    assert_eq!(jce_error(&e), &JceError::FrameLen(u32::MAX as usize));
+
+    let mut codec = codec.with_max_frame_len(8);
+    let e = codec.encode(&request(1), &mut dst).unwrap_err();
+    assert_eq!(jce_error(&e), &JceError::FrameLen(frame.len()));
+    assert!(dst.is_empty());
+
+    // a bad frame is an error, not a panic
+    let mut codec = JceCodec::<RequestPacket>::new();
+    let mut src = BytesMut::from(&[0, 0, 0, 6, 0x10, 0x01][..]);
+    let e = codec.decode(&mut src).unwrap_err();
+    assert_eq!(jce_error(&e).kind(), &JceError::TagNotFound(4));
+}
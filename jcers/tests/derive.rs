@@ -0,0 +1,518 @@
+use bytes::Bytes;
+use jcers::{JceError, JcePath};
+use jcers_proc::{JceGet, JcePut, JceTypeName};
+
+#[derive(Debug, Clone, PartialEq, Default, JceGet, JcePut, JceTypeName)]
+pub struct Outer {
+    #[jce(0)]
+    pub a: u8,
+    #[jce(1)]
+    pub inner: Inner,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, JceGet, JcePut)]
+pub struct Inner {
+    #[jce(0)]
+    pub name: String,
+}
+
+#[test]
+fn test_error_field_path() {
+    // { 0: 1, 1: { 0: 2u8 } }, name is not a string
+    let mut buf = Bytes::from(hex::decode("00011a00020b").unwrap());
+    let e = jcers::from_buf::<_, Outer>(&mut buf).unwrap_err();
+    assert_eq!(e.kind(), &JceError::ReadLenError(jcers::JceType::U8));
+    assert_eq!(
+        e.path(),
+        &[
+            JcePath::Field("Outer", "inner", 1),
+            JcePath::Field("Inner", "name", 0)
+        ]
+    );
+    assert!(e
+        .to_string()
+        .ends_with("path Outer.inner(1) -> Inner.name(0)"));
+}
+
+#[test]
+fn test_fields_out_of_order() {
+    // { 2: 5, 1: { 0: "n" }, 0: 1, 3: {} }
+    let data = hex::decode("20051a06016e0b00013a0b").unwrap();
+    let expected = Outer {
+        a: 1,
+        inner: Inner {
+            name: "n".to_owned(),
+        },
+    };
+    assert_eq!(
+        jcers::from_buf::<_, Outer>(&mut Bytes::from(data.clone())),
+        Ok(expected.clone())
+    );
+
+    // boxed in a list, each struct stops at its own end
+    let mut boxed = hex::decode("090002").unwrap();
+    boxed.extend(hex::decode("0a").unwrap());
+    boxed.extend(&data);
+    boxed.extend(hex::decode("0b0a1a0601").unwrap());
+    boxed.extend(hex::decode("6d0b00020b").unwrap());
+    let list: Vec<Outer> = jcers::from_buf(&mut Bytes::from(boxed)).unwrap();
+    assert_eq!(list[0], expected);
+    assert_eq!(list[1].inner.name, "m");
+
+    // a missing field is reported at the end of its struct
+    let e =
+        jcers::from_buf::<_, Outer>(&mut Bytes::from(hex::decode("0001").unwrap())).unwrap_err();
+    assert_eq!(e.kind(), &JceError::TagNotFound(1));
+    assert_eq!(e.path(), &[JcePath::Field("Outer", "inner", 1)]);
+}
+
+#[derive(Debug, Clone, PartialEq, Default, JceGet, JcePut)]
+pub struct Presence {
+    #[jce(0, require)]
+    pub a: i32,
+    #[jce(1, optional)]
+    pub b: String,
+    #[jce(2, default = 7)]
+    pub c: i64,
+    #[jce(3)]
+    pub d: Option<String>,
+    #[jce(4, optional, default = Some(5))]
+    pub e: Option<u8>,
+    #[jce(5, optional)]
+    pub f: Option<Inner>,
+}
+
+#[derive(Debug, PartialEq, JceGet)]
+pub struct BorrowedPresence<'a> {
+    #[jce(0)]
+    pub a: Option<&'a str>,
+    #[jce(1, default = "x")]
+    pub b: &'a str,
+}
+
+#[test]
+fn test_field_presence() {
+    use jcers::JcePut as _;
+
+    // { 0: 1 }
+    let value: Presence = jcers::from_buf(&mut Bytes::from(hex::decode("0001").unwrap())).unwrap();
+    assert_eq!(
+        value,
+        Presence {
+            a: 1,
+            b: String::new(),
+            c: 7,
+            d: None,
+            e: Some(5),
+            f: None,
+        }
+    );
+
+    // `None` is not written, `Some` is written as the value
+    let value = Presence {
+        d: Some("d".to_owned()),
+        e: None,
+        ..value
+    };
+    let bytes = value.freeze();
+    // { 0: 1, 1: "", 2: 7, 3: "d" }
+    assert_eq!(hex::encode(&bytes), "000116002007360164");
+    // a skipped `None` decodes to the default
+    assert_eq!(
+        jcers::from_buf(&mut bytes.clone()),
+        Ok(Presence {
+            e: Some(5),
+            ..value
+        })
+    );
+
+    // a required field is still an error
+    let e = jcers::from_buf::<_, Presence>(&mut Bytes::from(hex::decode("160162").unwrap()))
+        .unwrap_err();
+    assert_eq!(e.kind(), &JceError::TagNotFound(0));
+    assert_eq!(e.path(), &[JcePath::Field("Presence", "a", 0)]);
+
+    assert_eq!(
+        jcers::from_slice::<BorrowedPresence>(&[0x16, 0x01, b'y']),
+        Ok(BorrowedPresence { a: None, b: "y" })
+    );
+    assert_eq!(
+        jcers::from_slice::<BorrowedPresence>(&[0x06, 0x01, b'z']),
+        Ok(BorrowedPresence {
+            a: Some("z"),
+            b: "x"
+        })
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Default, JceGet, JcePut)]
+pub struct Pair(#[jce(0)] pub u8, #[jce(1, optional)] pub String);
+
+#[derive(Debug, Clone, PartialEq, Default, JceGet, JcePut)]
+#[jce(transparent)]
+pub struct Uin(pub i64);
+
+#[derive(Debug, Clone, PartialEq, Default, JceGet, JcePut)]
+#[jce(transparent)]
+pub struct Names {
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, JceGet)]
+#[jce(transparent)]
+pub struct Text<'a>(&'a str);
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, JceGet, JcePut)]
+pub enum Platform {
+    #[default]
+    Unknown,
+    Android = 2,
+    Ios,
+    Desktop = -70000,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, JceGet, JcePut)]
+pub struct Login {
+    #[jce(0)]
+    pub uin: Uin,
+    #[jce(1)]
+    pub platforms: Vec<Platform>,
+    #[jce(2, optional)]
+    pub platform: Platform,
+    #[jce(3)]
+    pub pair: Pair,
+}
+
+#[test]
+fn test_tuple_transparent_and_enum() {
+    use jcers::JcePut as _;
+
+    // { 0: 1, 1: "a" }
+    let pair = Pair(1, "a".to_owned());
+    assert_eq!(hex::encode(pair.freeze()), "0001160161");
+    assert_eq!(
+        jcers::from_buf(&mut Bytes::from(hex::decode("0001").unwrap())),
+        Ok(Pair(1, String::new()))
+    );
+    let e =
+        jcers::from_buf::<_, Pair>(&mut Bytes::from(hex::decode("160161").unwrap())).unwrap_err();
+    assert_eq!(e.path(), &[JcePath::Field("Pair", "0", 0)]);
+
+    // a transparent struct is written as its field
+    assert_eq!(Uin(7).freeze(), 7i64.freeze());
+    let names = Names {
+        names: vec!["x".to_owned()],
+    };
+    assert_eq!(names.freeze(), vec!["x".to_owned()].freeze());
+    assert_eq!(
+        jcers::from_slice::<Text>(&[0x06, 0x01, b't']),
+        Ok(Text("t"))
+    );
+
+    // an enum is written as its discriminant
+    assert_eq!(Platform::Ios.freeze(), Bytes::from_static(&[0x00, 0x03]));
+    assert_eq!(Platform::Unknown.freeze(), Bytes::from_static(&[0x0c]));
+    let login = Login {
+        uin: Uin(10001),
+        platforms: vec![Platform::Android, Platform::Desktop],
+        platform: Platform::Ios,
+        pair: Pair(2, "b".to_owned()),
+    };
+    let bytes = login.freeze();
+    assert_eq!(jcers::from_buf(&mut bytes.clone()), Ok(login));
+    // { 0: 10001, 1: [0], 3: { 0: 0 } }, a missing optional enum is the default
+    let login: Login = jcers::from_buf(&mut Bytes::from(
+        hex::decode("0127111900010c3a0c0b").unwrap(),
+    ))
+    .unwrap();
+    assert_eq!(login.platform, Platform::Unknown);
+    assert_eq!(login.platforms, [Platform::Unknown]);
+
+    // { 0: 5 } is not a Platform
+    let e = jcers::from_buf::<_, Platform>(&mut Bytes::from_static(&[0x00, 0x05])).unwrap_err();
+    assert_eq!(e.kind(), &JceError::UnknownEnumValue("Platform", 5));
+}
+
+#[derive(Debug, Default, PartialEq, JceGet, JcePut)]
+pub struct Page<T> {
+    #[jce(0)]
+    pub items: Vec<T>,
+    #[jce(1)]
+    pub next: Option<i32>,
+}
+
+#[derive(Debug, PartialEq, JceGet)]
+pub struct Entry<'a, 'b, T>
+where
+    T: Copy,
+{
+    #[jce(0)]
+    pub key: &'a str,
+    #[jce(1)]
+    pub value: &'b [u8],
+    #[jce(2)]
+    pub extra: T,
+}
+
+pub trait Message {
+    type Id;
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Ping;
+
+impl Message for Ping {
+    type Id = i64;
+}
+
+// `M` itself is never read or written
+#[derive(Debug, PartialEq, JceGet, JcePut)]
+#[jce(bound(get = "M::Id: jcers::JceGet + Default", put = "M::Id: jcers::JcePut"))]
+pub struct Tagged<M: Message> {
+    #[jce(0)]
+    pub id: M::Id,
+}
+
+impl<M: Message> Default for Tagged<M>
+where
+    M::Id: Default,
+{
+    fn default() -> Self {
+        Tagged {
+            id: M::Id::default(),
+        }
+    }
+}
+
+#[test]
+fn test_generics() {
+    use jcers::JcePut as _;
+
+    let page = Page {
+        items: vec![
+            Inner {
+                name: "a".to_owned(),
+            },
+            Inner {
+                name: "b".to_owned(),
+            },
+        ],
+        next: Some(3),
+    };
+    assert_eq!(jcers::from_buf(&mut page.freeze()), Ok(page));
+    // { 0: [1, 2], 1: 0 }
+    let page: Page<i32> =
+        jcers::from_buf(&mut Bytes::from(hex::decode("090002000100021c").unwrap())).unwrap();
+    assert_eq!(page.items, [1, 2]);
+    assert_eq!(page.next, Some(0));
+
+    // { 0: "k", 1: b"v", 2: 4 }
+    assert_eq!(
+        jcers::from_slice::<Entry<i32>>(&hex::decode("06016b1d000001762004").unwrap()),
+        Ok(Entry {
+            key: "k",
+            value: b"v",
+            extra: 4
+        })
+    );
+
+    let tagged: Tagged<Ping> = Tagged { id: 9 };
+    let bytes = tagged.freeze();
+    assert_eq!(hex::encode(&bytes), "0009");
+    assert_eq!(jcers::from_buf(&mut bytes.clone()), Ok(tagged));
+}
+
+/// An IPv4 address as the `i64` of its bits
+mod ipv4 {
+    use std::net::Ipv4Addr;
+
+    pub fn jce_get<B: bytes::Buf + ?Sized>(jce: &mut jcers::Jce<B>) -> jcers::JceResult<Ipv4Addr> {
+        let bits: i64 = jcers::JceGet::jce_get(jce)?;
+        Ok(Ipv4Addr::from(bits as u32))
+    }
+
+    pub fn jce_put(addr: &Ipv4Addr, jce_mut: &mut jcers::JceMut, tag: u8) {
+        jcers::JcePut::jce_put(&(u32::from(*addr) as i64), jce_mut, tag)
+    }
+}
+
+#[derive(Debug, PartialEq, JceGet, JcePut)]
+pub struct Peer {
+    #[jce(0, with = "ipv4")]
+    pub addr: std::net::Ipv4Addr,
+    #[jce(1, optional, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[jce(2, with = "ipv4", default = std::net::Ipv4Addr::LOCALHOST)]
+    pub gateway: std::net::Ipv4Addr,
+    #[jce(skip)]
+    pub seen: Option<u32>,
+    #[jce(skip, default = 3)]
+    pub retries: u8,
+}
+
+impl Default for Peer {
+    fn default() -> Self {
+        Peer {
+            addr: std::net::Ipv4Addr::UNSPECIFIED,
+            name: String::new(),
+            gateway: std::net::Ipv4Addr::UNSPECIFIED,
+            seen: None,
+            retries: 0,
+        }
+    }
+}
+
+#[test]
+fn test_with_and_skip() {
+    use jcers::JcePut as _;
+
+    let peer = Peer {
+        addr: [127, 0, 0, 1].into(),
+        gateway: [10, 0, 0, 1].into(),
+        seen: Some(1),
+        ..Default::default()
+    };
+    let bytes = peer.freeze();
+    // { 0: 0x7f000001, 2: 0x0a000001 }, the empty name and skipped fields are not written
+    assert_eq!(hex::encode(&bytes), "027f000001220a000001");
+    assert_eq!(
+        jcers::from_buf(&mut bytes.clone()),
+        Ok(Peer {
+            seen: None,
+            retries: 3,
+            ..peer
+        })
+    );
+
+    // { 0: 0, 1: "a" }, the module reads the Empty head
+    let peer: Peer = jcers::from_buf(&mut Bytes::from(hex::decode("0c160161").unwrap())).unwrap();
+    assert_eq!(peer.addr, std::net::Ipv4Addr::UNSPECIFIED);
+    assert_eq!(peer.name, "a");
+    assert_eq!(peer.gateway, std::net::Ipv4Addr::LOCALHOST);
+
+    let e =
+        jcers::from_buf::<_, Peer>(&mut Bytes::from(hex::decode("160161").unwrap())).unwrap_err();
+    assert_eq!(e.path(), &[JcePath::Field("Peer", "addr", 0)]);
+}
+
+/// A `Wrapping<T>`, as its `T`
+mod wrapping {
+    use std::num::Wrapping;
+
+    pub fn jce_get<T, B>(jce: &mut jcers::Jce<B>) -> jcers::JceResult<Wrapping<T>>
+    where
+        T: jcers::JceGet,
+        B: bytes::Buf + ?Sized,
+    {
+        jcers::JceGet::jce_get(jce).map(Wrapping)
+    }
+
+    pub fn jce_put<T: jcers::JcePut>(v: &Wrapping<T>, jce_mut: &mut jcers::JceMut, tag: u8) {
+        jcers::JcePut::jce_put(&v.0, jce_mut, tag)
+    }
+}
+
+#[derive(Debug, PartialEq, Default, JceGet, JcePut)]
+pub struct Counter<T> {
+    #[jce(0, with = "wrapping")]
+    pub count: std::num::Wrapping<T>,
+    #[jce(1)]
+    pub step: T,
+}
+
+#[test]
+fn test_with_generics() {
+    use jcers::JcePut as _;
+
+    let counter = Counter {
+        count: std::num::Wrapping(7u8),
+        step: 1,
+    };
+    let bytes = counter.freeze();
+    assert_eq!(hex::encode(&bytes), "00071001");
+    assert_eq!(jcers::from_buf(&mut bytes.clone()), Ok(counter));
+
+    // { 0: "a" }, an error of the module is reported under the field
+    let e = jcers::from_buf::<_, Counter<u8>>(&mut Bytes::from(hex::decode("060161").unwrap()))
+        .unwrap_err();
+    assert_eq!(e.path(), &[JcePath::Field("Counter", "count", 0)]);
+}
+
+#[derive(Debug, Clone, PartialEq, Default, JceGet, JcePut)]
+pub struct ServerV2 {
+    #[jce(0)]
+    pub id: i32,
+    #[jce(1)]
+    pub region: String,
+    #[jce(2)]
+    pub name: String,
+    #[jce(4)]
+    pub weights: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, JceGet, JcePut)]
+pub struct ServerV1 {
+    #[jce(0)]
+    pub id: i32,
+    #[jce(2)]
+    pub name: String,
+    #[jce(unknown)]
+    pub unknown: jcers::JceStruct,
+}
+
+#[test]
+fn test_unknown_fields() {
+    use jcers::{JcePut as _, JceValue};
+
+    let v2 = ServerV2 {
+        id: 7,
+        region: "eu".to_owned(),
+        name: "a".to_owned(),
+        weights: vec![1, 2],
+    };
+    let bytes = v2.freeze();
+    let v1: ServerV1 = jcers::from_buf(&mut bytes.clone()).unwrap();
+    assert_eq!(v1.id, 7);
+    assert_eq!(v1.name, "a");
+    assert_eq!(
+        v1.unknown,
+        jcers::JceStruct::from([
+            (1, JceValue::String("eu".to_owned())),
+            (4, JceValue::List(vec![JceValue::U8(1), JceValue::U8(2)])),
+        ])
+    );
+    // written back between the known fields
+    assert_eq!(v1.freeze(), bytes);
+    assert_eq!(jcers::from_buf(&mut v1.freeze()), Ok(v2));
+}
+
+#[derive(JceTypeName)]
+#[jce(name = "Demo.Server")]
+pub struct NamedServer {
+    pub id: i32,
+}
+
+#[derive(JceTypeName)]
+#[jce(transparent)]
+pub struct Ids(pub Vec<i64>);
+
+#[derive(JceTypeName)]
+pub enum Level {
+    Low,
+}
+
+#[test]
+fn test_type_names() {
+    use jcers::JceTypeName as _;
+
+    assert_eq!(i32::type_name(), "int32");
+    assert_eq!(<Vec<String>>::type_name(), "list<string>");
+    assert_eq!(Bytes::type_name(), "list<char>");
+    assert_eq!(
+        <jcers::SortedMap<i64, NamedServer>>::type_name(),
+        "map<int64,Demo.Server>"
+    );
+    assert_eq!(Ids::type_name(), "list<int64>");
+    assert_eq!(Level::type_name(), "int32");
+    assert_eq!(Outer::type_name(), "Outer");
+}
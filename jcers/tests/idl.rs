@@ -0,0 +1,63 @@
+#![cfg(feature = "idl")]
+
+use bytes::Bytes;
+use jcers::JcePut as _;
+
+jcers::jce_idl! {
+    #include "tests/data/common.jce"
+
+    module Inline
+    {
+        const string GREETING = "hi";
+
+        struct Bar
+        {
+            0 require int a;
+            1 optional string b = "x";
+            2 optional map<string, vector<float>> c;
+            3 optional short d = -3;
+            4 optional Common::Device device;
+            5 optional double e = 1.5f;
+        };
+    };
+}
+
+mod included {
+    jcers::jce_include!("tests/data/login.jce");
+}
+
+#[test]
+fn test_jce_idl() {
+    let bar = inline::Bar::default();
+    assert_eq!(bar.b, "x");
+    assert_eq!(bar.d, -3);
+    assert_eq!(bar.e, 1.5);
+    assert_eq!(inline::GREETING, "hi");
+    assert_eq!(bar.device.platform, common::Platform::Ios as i32);
+
+    let bar = inline::Bar {
+        a: 7,
+        c: [("k".to_owned(), vec![0.5f32])].into(),
+        ..Default::default()
+    };
+    let decoded: inline::Bar = jcers::from_buf(&mut bar.freeze()).unwrap();
+    assert_eq!(decoded, bar);
+}
+
+#[test]
+fn test_jce_include() {
+    use included::{common, login};
+
+    let req = login::LoginReq {
+        uin: 10001,
+        password: Bytes::from_static(b"pw"),
+        device: common::Device {
+            name: "phone".to_owned(),
+            platform: common::Platform::Android as i32,
+        },
+        ..Default::default()
+    };
+    assert_eq!(req.retry, -1);
+    let decoded: login::LoginReq = jcers::from_buf(&mut req.freeze()).unwrap();
+    assert_eq!(decoded, req);
+}
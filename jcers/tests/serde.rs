@@ -0,0 +1,188 @@
+#![cfg(feature = "serde")]
+
+use std::collections::HashMap;
+
+use bytes::Bytes;
+use jcers::JcePut as _;
+use jcers::{JceError, JcePath};
+use jcers_proc::{JceGet, JcePut};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct TestStruct {
+    #[serde(rename = "0")]
+    pub a: u8,
+    #[serde(rename = "1")]
+    pub b: i32,
+    #[serde(rename = "2")]
+    pub c: f32,
+    #[serde(rename = "3")]
+    pub d: String,
+    #[serde(rename = "6")]
+    pub e: HashMap<u8, u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JceGet, JcePut)]
+pub struct Mixed {
+    #[jce(0)]
+    pub a: i64,
+    #[jce(1)]
+    pub b: Vec<String>,
+    #[jce(2)]
+    pub c: Bytes,
+    #[jce(3)]
+    pub d: Inner,
+    #[jce(4)]
+    pub e: HashMap<String, i16>,
+    #[jce(5)]
+    pub f: f64,
+    #[jce(6)]
+    pub g: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JceGet, JcePut)]
+pub struct Inner {
+    #[jce(0)]
+    pub a: i32,
+    #[jce(1)]
+    pub b: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Positional(u8, String, Option<i32>);
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WithOption {
+    #[serde(rename = "1")]
+    pub a: Option<i32>,
+    #[serde(rename = "3")]
+    pub b: Option<String>,
+    #[serde(rename = "4")]
+    pub color: Color,
+    #[serde(rename = "5")]
+    pub list: Vec<Color>,
+}
+
+fn mixed() -> Mixed {
+    Mixed {
+        a: -1234567890123,
+        b: vec!["a".to_owned(), "b".repeat(300)],
+        c: Bytes::from_static(b"\x00\x01\x02"),
+        d: Inner {
+            a: -1,
+            b: "inner".to_owned(),
+        },
+        e: HashMap::from([("k".to_owned(), -300)]),
+        f: 0.5,
+        g: true,
+    }
+}
+
+#[test]
+fn test_serde_decode_vector() {
+    let data = hex::decode("0a0c1c24123456783604746573744c5c680001000110020b").unwrap();
+    let t: TestStruct = jcers::from_bytes(&data).unwrap();
+    assert_eq!(
+        t,
+        TestStruct {
+            a: 0,
+            b: 0,
+            c: f32::from_be_bytes([0x12, 0x34, 0x56, 0x78]),
+            d: "test".to_owned(),
+            e: HashMap::from([(1, 2)]),
+        }
+    );
+}
+
+#[test]
+fn test_serde_same_wire_as_derive() {
+    let value = mixed();
+    let bytes = jcers::to_bytes(&value).unwrap();
+    assert_eq!(bytes, value.freeze());
+    let decoded: Mixed = jcers::from_bytes(&bytes).unwrap();
+    assert_eq!(decoded, value);
+    let decoded: Mixed = jcers::from_buf(&mut bytes.clone()).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[test]
+fn test_serde_option_enum_tuple() {
+    let value = WithOption {
+        a: None,
+        b: Some("x".to_owned()),
+        color: Color::Blue,
+        list: vec![Color::Red, Color::Green],
+    };
+    let bytes = jcers::to_bytes(&value).unwrap();
+    assert_eq!(hex::decode("36017840025900020c0001").unwrap(), &bytes[..]);
+    assert_eq!(jcers::from_bytes::<WithOption>(&bytes).unwrap(), value);
+
+    let value = Positional(7, "p".to_owned(), Some(-2));
+    let bytes = jcers::to_bytes(&value).unwrap();
+    assert_eq!(hex::decode("000716017020fe").unwrap(), &bytes[..]);
+    assert_eq!(jcers::from_bytes::<Positional>(&bytes).unwrap(), value);
+}
+
+#[test]
+fn test_serde_none_in_collections() {
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Lists {
+        a: Vec<Option<i32>>,
+        b: HashMap<String, Option<i32>>,
+        c: i32,
+    }
+
+    // present values are written as themselves
+    let value = Lists {
+        a: vec![Some(1), Some(3)],
+        b: HashMap::from([("k".to_owned(), Some(2))]),
+        c: 7,
+    };
+    let bytes = jcers::to_bytes(&value).unwrap();
+    assert_eq!(jcers::from_bytes::<Lists>(&bytes).unwrap(), value);
+
+    // a None would leave the length counting a missing element
+    let value = Lists {
+        a: vec![Some(1), None, Some(3)],
+        b: HashMap::new(),
+        c: 7,
+    };
+    assert_eq!(
+        jcers::to_bytes(&value),
+        Err(JceError::WriteError(
+            "None is only supported as a struct field"
+        ))
+    );
+    let value = Lists {
+        a: vec![],
+        b: HashMap::from([("k".to_owned(), None)]),
+        c: 7,
+    };
+    assert_eq!(
+        jcers::to_bytes(&value),
+        Err(JceError::WriteError(
+            "None is only supported as a struct field"
+        ))
+    );
+    assert_eq!(
+        jcers::to_bytes(&None::<i32>),
+        Err(JceError::WriteError(
+            "None is only supported as a struct field"
+        ))
+    );
+}
+
+#[test]
+fn test_serde_error_path() {
+    // { 4: 7 }, no such Color variant
+    let e = jcers::from_bytes::<WithOption>(&[0x40, 0x07]).unwrap_err();
+    assert!(matches!(e.kind(), JceError::Message(_)));
+    assert_eq!(e.path(), &[JcePath::Tag(4)]);
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+
+use bytes::{Bytes, BytesMut};
+use jcers::tars::{self, RequestPacket, ResponsePacket, UniAttribute, UniPacket};
+use jcers::{JceError, JcePath, JcePut};
+
+// Where each frame comes from. None is a capture from a TarsCpp / TarsJava
+// peer yet: they are laid out by hand from the field lists of
+// RequestPacket.tars / ResponsePacket.tars, with integers in their smallest
+// type, zero as Empty and every field written. A capture replacing one should
+// say here which peer, version and call it was taken from.
+
+/// source: hand-built, `request()`
+const REQUEST: &str = "0000003b\
+    10012c3c40015619546573742e48656c6c6f5365727665722e48656c6c6f4f626a\
+    660568656c6c6f7d00000416026869810bb8980ca80c";
+/// source: hand-built, the response in `test_packets`
+const RESPONSE: &str = "00000016\
+    10012c30014c5c6d0000010c780c8600980c";
+/// source: hand-built, the TUP version 3 packet in `test_uni_packet`, its
+/// buffer is { 0: { "a": [0x00, 0x05] } }
+const UNI_PACKET_V3: &str = "00000025\
+    10032c3c4001560153660166\
+    7d00000c0800010601611d0000020005\
+    8c980ca80c";
+
+fn request() -> RequestPacket {
+    RequestPacket {
+        version: tars::TARS_VERSION,
+        packet_type: tars::NORMAL,
+        message_type: 0,
+        request_id: 1,
+        servant_name: "Test.HelloServer.HelloObj".to_owned(),
+        func_name: "hello".to_owned(),
+        // { 1: "hi" }
+        buffer: Bytes::from_static(&[0x16, 0x02, b'h', b'i']),
+        timeout: 3000,
+        context: HashMap::new(),
+        status: HashMap::new(),
+    }
+}
+
+#[test]
+fn test_packets() {
+    let mut src = BytesMut::from(&hex::decode(REQUEST).unwrap()[..]);
+    assert_eq!(tars::decode_frame(&mut src), Ok(Some(request())));
+    assert!(src.is_empty());
+    let mut dst = BytesMut::new();
+    tars::encode_frame(&request(), &mut dst);
+    assert_eq!(hex::encode(&dst), REQUEST);
+
+    let response = ResponsePacket {
+        version: tars::TARS_VERSION,
+        request_id: 1,
+        ret: tars::SERVER_SUCCESS,
+        // { 0: 0 }
+        buffer: Bytes::from_static(&[0x0c]),
+        ..Default::default()
+    };
+    let mut src = BytesMut::from(&hex::decode(RESPONSE).unwrap()[..]);
+    assert_eq!(tars::decode_frame(&mut src), Ok(Some(response.clone())));
+    let mut dst = BytesMut::new();
+    tars::encode_frame(&response, &mut dst);
+    assert_eq!(hex::encode(&dst), RESPONSE);
+
+    // optional fields default, required ones are an error
+    let packet: ResponsePacket =
+        jcers::from_buf(&mut Bytes::from(hex::decode("100130016d0000010c").unwrap())).unwrap();
+    assert_eq!(packet, response);
+    let e =
+        jcers::from_buf::<_, ResponsePacket>(&mut Bytes::from(hex::decode("10013001").unwrap()))
+            .unwrap_err();
+    assert_eq!(e.kind(), &JceError::TagNotFound(6));
+    assert_eq!(e.path(), &[JcePath::Field("ResponsePacket", "buffer", 6)]);
+    assert_eq!(response.freeze(), hex::decode(&RESPONSE[8..]).unwrap());
+}
+
+#[test]
+fn test_partial_frames() {
+    let data = hex::decode(format!("{}{}", REQUEST, REQUEST)).unwrap();
+    let mut src = BytesMut::new();
+    let mut packets = vec![];
+    // fed a few bytes at a time, a frame is decoded once complete
+    for chunk in data.chunks(5) {
+        src.extend_from_slice(chunk);
+        while let Some(packet) = tars::decode_frame::<RequestPacket>(&mut src).unwrap() {
+            packets.push(packet);
+        }
+    }
+    assert_eq!(packets, [request(), request()]);
+    assert!(src.is_empty());
+
+    assert_eq!(tars::frame_len(&[0, 0, 0], 100), Ok(None));
+    assert_eq!(tars::frame_len(&[0, 0, 0, 9, 1], 100), Ok(Some(9)));
+    assert_eq!(
+        tars::frame_len(&[0, 0, 0, 3], 100),
+        Err(JceError::FrameLen(3))
+    );
+    let mut src = BytesMut::from(&[0x01, 0, 0, 0][..]);
+    assert_eq!(
+        tars::decode_frame::<RequestPacket>(&mut src),
+        Err(JceError::FrameLen(1 << 24))
+    );
+}
+
+#[test]
+fn test_uni_packet() {
+    let mut packet = UniPacket::new(tars::TUP_VERSION3).unwrap();
+    packet.request.request_id = 1;
+    packet.request.servant_name = "S".to_owned();
+    packet.request.func_name = "f".to_owned();
+    packet.put("a", &5i32);
+    let mut dst = BytesMut::new();
+    packet.encode(&mut dst);
+    assert_eq!(hex::encode(&dst), UNI_PACKET_V3);
+    let decoded = UniPacket::decode(&mut dst).unwrap().unwrap();
+    assert_eq!(decoded, packet);
+    assert_eq!(decoded.get::<i32>("a"), Ok(5));
+    assert_eq!(
+        decoded.get::<i32>("b"),
+        Err(JceError::AttributeNotFound("b".to_owned()))
+    );
+
+    let mut response = decoded.create_response();
+    response.put("", &0i32);
+    response.put("names", &vec!["x".to_owned()]);
+    let request = response.to_request();
+    assert_eq!((request.request_id, request.func_name.as_str()), (1, "f"));
+    let response = UniPacket::from_request(request).unwrap();
+    assert_eq!(
+        response.get::<Vec<String>>("names"),
+        Ok(vec!["x".to_owned()])
+    );
+}
+
+#[test]
+fn test_uni_version() {
+    for version in [0, tars::TARS_VERSION, 4, -1] {
+        assert_eq!(
+            UniPacket::new(version),
+            Err(JceError::UnsupportedVersion(version))
+        );
+        assert_eq!(
+            UniAttribute::decode(version, Bytes::from_static(&[0x08, 0x0c])),
+            Err(JceError::UnsupportedVersion(version))
+        );
+    }
+    // a tars packet is not a TUP one
+    let mut src = BytesMut::from(&hex::decode(REQUEST).unwrap()[..]);
+    assert_eq!(
+        UniPacket::decode(&mut src),
+        Err(JceError::UnsupportedVersion(tars::TARS_VERSION))
+    );
+
+    let packet = UniPacket::new(tars::TUP_VERSION2).unwrap();
+    assert_eq!(packet.version(), tars::TUP_VERSION2);
+    let response = packet.create_response();
+    assert_eq!(response.version(), tars::TUP_VERSION2);
+    assert_eq!(response.request.version, tars::TUP_VERSION2);
+}
+
+#[test]
+fn test_uni_attribute_v2() {
+    let mut attribute = UniAttribute::new(tars::TUP_VERSION2).unwrap();
+    attribute.put("id", &7i64);
+    attribute.put("name", "n");
+    attribute.put("scores", &HashMap::from([(1u8, vec![2.5f32])]));
+    attribute.put("request", &request());
+    let buffer = attribute.encode();
+
+    // each value is kept under its type name
+    let data: HashMap<String, HashMap<String, Bytes>> =
+        jcers::from_buf_with_tag(&mut buffer.clone(), 0).unwrap();
+    let mut types: Vec<_> = data
+        .iter()
+        .map(|(name, types)| (name.as_str(), types.keys().next().unwrap().as_str()))
+        .collect();
+    types.sort();
+    assert_eq!(
+        types,
+        [
+            ("id", "int64"),
+            ("name", "string"),
+            ("request", "tars.RequestPacket"),
+            ("scores", "map<char,list<float>>"),
+        ]
+    );
+    assert_eq!(data["id"]["int64"], Bytes::from_static(&[0x00, 0x07]));
+
+    let attribute = UniAttribute::decode(tars::TUP_VERSION2, buffer).unwrap();
+    assert!(attribute.contains("name"));
+    assert_eq!(attribute.get::<String>("name"), Ok("n".to_owned()));
+    assert_eq!(attribute.get::<RequestPacket>("request"), Ok(request()));
+    assert_eq!(
+        attribute.get::<HashMap<u8, Vec<f32>>>("scores"),
+        Ok(HashMap::from([(1, vec![2.5])]))
+    );
+}
@@ -0,0 +1,11 @@
+use jcers_proc::{JceGet, JcePut};
+
+#[derive(Default, JceGet, JcePut)]
+struct Foo {
+    #[jce(0)]
+    a: i32,
+    #[jce(0)]
+    b: i32,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use jcers_proc::{JceGet, JcePut};
+
+#[derive(Default, JceGet, JcePut)]
+struct Foo {
+    #[jce("0")]
+    a: i32,
+    #[jce(1.5)]
+    b: i32,
+}
+
+fn main() {}
@@ -0,0 +1,13 @@
+#![deny(deprecated)]
+
+use jcers_proc::JceGet;
+
+#[derive(Default, JceGet)]
+struct Foo {
+    #[jce(1)]
+    a: i32,
+    #[jce(0)]
+    b: i32,
+}
+
+fn main() {}
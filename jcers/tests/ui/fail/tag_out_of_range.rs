@@ -0,0 +1,9 @@
+use jcers_proc::{JceGet, JcePut};
+
+#[derive(Default, JceGet, JcePut)]
+struct Foo {
+    #[jce(300)]
+    a: i32,
+}
+
+fn main() {}
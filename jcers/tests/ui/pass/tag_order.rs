@@ -0,0 +1,17 @@
+use jcers_proc::{JceGet, JcePut};
+
+// tags out of order only warn, `tag_order_denied` shows the warning
+#[derive(Default, JceGet, JcePut)]
+struct Foo {
+    #[jce(1)]
+    a: i32,
+    #[jce(0)]
+    b: i32,
+}
+
+fn main() {
+    let foo = Foo { a: 1, b: 2 };
+    let bytes = jcers::JcePut::freeze(&foo);
+    let foo: Foo = jcers::from_buf(&mut bytes.clone()).unwrap();
+    assert_eq!((foo.a, foo.b), (1, 2));
+}
@@ -0,0 +1,632 @@
+use std::collections::{BTreeMap, HashMap};
+
+use jcers_idl::ast::{self, Item, TypeKind, ValueKind};
+use jcers_idl::{Error, Span};
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::quote;
+
+use super::{Config, MapType, Source};
+
+const HEADER: &str = "// This file is @generated by jcers_build, do not edit.\n\n";
+
+/// Error at a span of `sources[source]`
+pub(crate) struct GenError {
+    pub(crate) source: usize,
+    pub(crate) error: Error,
+}
+
+/// Paths of the extra derives
+pub(crate) fn derives(config: &Config) -> Result<Vec<syn::Path>, String> {
+    config
+        .derives
+        .iter()
+        .map(|d| syn::parse_str::<syn::Path>(d).map_err(|_| format!("invalid derive `{}`", d)))
+        .collect()
+}
+
+/// Code of every module in `sources`, by the module name in snake case
+pub(crate) fn modules(
+    config: &Config,
+    derives: Vec<syn::Path>,
+    sources: &[Source],
+) -> Result<BTreeMap<String, TokenStream>, GenError> {
+    let scope = Scope::new(sources)?;
+    let gen = Gen {
+        config,
+        derives,
+        scope: &scope,
+    };
+    let mut out = BTreeMap::new();
+    for (name, parts) in &scope.modules {
+        out.insert(snake(name), gen.module(parts)?);
+    }
+    Ok(out)
+}
+
+/// Pretty print the code of a module into a file
+pub(crate) fn format(tokens: TokenStream) -> String {
+    let file: syn::File = syn::parse2(tokens).expect("generated code should parse");
+    format!("{}{}", HEADER, prettyplease::unparse(&file))
+}
+
+/// A module in one file, a module may be split over several files
+struct Part<'a> {
+    source: &'a Source,
+    module: &'a ast::Module,
+}
+
+impl Part<'_> {
+    fn name(&self) -> &str {
+        &self.module.name.name
+    }
+
+    /// Error at `span` of this file
+    fn error(&self, span: Span, message: impl Into<String>) -> GenError {
+        GenError {
+            source: self.source.index,
+            error: Error::new(&self.source.src, span, message),
+        }
+    }
+}
+
+enum Symbol<'a> {
+    Struct,
+    /// variants with their values, and whether the value is taken by an
+    /// earlier variant
+    Enum(Vec<(&'a ast::Variant, i32, bool)>),
+    Const,
+}
+
+/// Items of all modules, by module and name
+struct Scope<'a> {
+    modules: BTreeMap<&'a str, Vec<Part<'a>>>,
+    symbols: HashMap<(&'a str, &'a str), Symbol<'a>>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(sources: &'a [Source]) -> Result<Self, GenError> {
+        let mut scope = Scope {
+            modules: BTreeMap::new(),
+            symbols: HashMap::new(),
+        };
+        for source in sources {
+            for module in &source.file.modules {
+                let part = Part { source, module };
+                for item in &module.items {
+                    let (name, symbol) = match item {
+                        Item::Struct(s) => (&s.name, Symbol::Struct),
+                        Item::Enum(e) => (&e.name, Symbol::Enum(enum_values(&part, e)?)),
+                        Item::Const(c) => (&c.name, Symbol::Const),
+                        Item::Key(_) | Item::Interface(_) => continue,
+                    };
+                    let key = (module.name.name.as_str(), name.name.as_str());
+                    if scope.symbols.insert(key, symbol).is_some() {
+                        return Err(part.error(
+                            name.span,
+                            format!(
+                                "`{}` is already defined in module `{}`",
+                                name.name,
+                                part.name()
+                            ),
+                        ));
+                    }
+                }
+                scope
+                    .modules
+                    .entry(module.name.name.as_str())
+                    .or_default()
+                    .push(part);
+            }
+        }
+        Ok(scope)
+    }
+
+    /// The item at `path` seen from `module`, with the module it is in
+    fn lookup<'p>(
+        &'p self,
+        module: &'p str,
+        path: &'p ast::Path,
+    ) -> Option<(&'p str, &'p Symbol<'a>)> {
+        let module = path.module().map_or(module, |m| m.name.as_str());
+        if path.segments.len() > 2 {
+            return None;
+        }
+        self.symbols
+            .get(&(module, path.name().name.as_str()))
+            .map(|symbol| (module, symbol))
+    }
+}
+
+/// Values of the variants, counting up from the previous one
+fn enum_values<'a>(
+    part: &Part,
+    e: &'a ast::Enum,
+) -> Result<Vec<(&'a ast::Variant, i32, bool)>, GenError> {
+    if e.variants.is_empty() {
+        return Err(part.error(
+            e.name.span,
+            format!("enum `{}` has no variants", e.name.name),
+        ));
+    }
+    let mut values: Vec<(&ast::Variant, i32, bool)> = vec![];
+    let mut next = 0i64;
+    for variant in &e.variants {
+        let value = match &variant.value {
+            None => next,
+            Some(ast::Value {
+                kind: ValueKind::Int(v),
+                ..
+            }) => *v,
+            Some(ast::Value {
+                kind: ValueKind::Path(p),
+                span,
+            }) => {
+                let same_enum = match p.segments.len() {
+                    1 => true,
+                    2 => p.segments[0].name == e.name.name,
+                    _ => false,
+                };
+                match values
+                    .iter()
+                    .find(|(v, ..)| same_enum && v.name.name == p.name().name)
+                {
+                    Some((_, v, _)) => *v as i64,
+                    None => {
+                        return Err(part.error(
+                            *span,
+                            format!(
+                                "`{}` is not an earlier variant of `{}`",
+                                p.name().name,
+                                e.name.name
+                            ),
+                        ))
+                    }
+                }
+            }
+            Some(value) => {
+                return Err(part.error(value.span, "enum value should be an integer"));
+            }
+        };
+        let value = i32::try_from(value).map_err(|_| {
+            part.error(
+                variant.span,
+                format!("value of `{}` is out of range for `int`", variant.name.name),
+            )
+        })?;
+        let alias = values.iter().any(|(_, v, _)| *v == value);
+        values.push((variant, value, alias));
+        next = value as i64 + 1;
+    }
+    Ok(values)
+}
+
+struct Gen<'a> {
+    config: &'a Config,
+    derives: Vec<syn::Path>,
+    scope: &'a Scope<'a>,
+}
+
+impl Gen<'_> {
+    fn module(&self, parts: &[Part]) -> Result<TokenStream, GenError> {
+        let mut ts = TokenStream::new();
+        for part in parts {
+            for item in &part.module.items {
+                ts.extend(match item {
+                    Item::Struct(s) => self.gen_struct(part, s)?,
+                    Item::Enum(e) => self.gen_enum(part, e),
+                    Item::Const(c) => self.gen_const(part, c)?,
+                    Item::Key(_) | Item::Interface(_) => continue,
+                });
+            }
+        }
+        Ok(ts)
+    }
+
+    fn gen_struct(&self, part: &Part, s: &ast::Struct) -> Result<TokenStream, GenError> {
+        let name = ident(&s.name.name);
+        let derives = &self.derives;
+        let mut fields = vec![];
+        let mut tys = vec![];
+        let mut attrs = vec![];
+        let mut defaults = vec![];
+        // written in ascending tags whatever the IDL order is
+        let mut sorted: Vec<_> = s.fields.iter().collect();
+        sorted.sort_by_key(|field| field.tag);
+        for field in sorted {
+            fields.push(ident(&snake(&field.name.name)));
+            tys.push(self.ty(part, &field.ty)?);
+            let tag = Literal::u8_unsuffixed(field.tag);
+            let default = match &field.default {
+                Some(value) => Some(self.value(part, &field.ty, value, true)?),
+                None => None,
+            };
+            // a missing optional field decodes to its IDL default
+            attrs.push(match (field.require, &default) {
+                (true, _) => quote!(#tag, require),
+                (false, Some(default)) => quote!(#tag, default = #default),
+                (false, None) => quote!(#tag, optional),
+            });
+            defaults.push(default.unwrap_or_else(|| quote!(::std::default::Default::default())));
+        }
+        let type_name = format!("{}.{}", part.name(), s.name.name);
+        Ok(quote! {
+            #[derive(Debug, Clone, PartialEq, jcers::JceGet, jcers::JcePut, jcers::JceTypeName #(, #derives)*)]
+            #[jce(name = #type_name)]
+            pub struct #name {
+                #(
+                    #[jce(#attrs)]
+                    pub #fields: #tys,
+                )*
+            }
+
+            impl ::std::default::Default for #name {
+                fn default() -> Self {
+                    #name {
+                        #(#fields: #defaults,)*
+                    }
+                }
+            }
+        })
+    }
+
+    fn gen_enum(&self, part: &Part, e: &ast::Enum) -> TokenStream {
+        let name = ident(&e.name.name);
+        let derives = &self.derives;
+        let values = match self.scope.symbols.get(&(part.name(), e.name.name.as_str())) {
+            Some(Symbol::Enum(values)) => values,
+            _ => unreachable!("enums are in the scope"),
+        };
+        let mut variants = vec![];
+        let mut discriminants = vec![];
+        let mut aliases = vec![];
+        for (variant, value, alias) in values {
+            let value = int_lit(*value as i64);
+            if *alias {
+                let alias = ident(&variant.name.name.to_uppercase());
+                let target = variant_ident(self.variant(values, &variant.name.name));
+                aliases.push(quote!(pub const #alias: #name = #name::#target;));
+            } else {
+                variants.push(variant_ident(&variant.name.name));
+                discriminants.push(value);
+            }
+        }
+        let first = &variants[0];
+        let rest = &variants[1..];
+        let rest_discriminants = &discriminants[1..];
+        let first_discriminant = &discriminants[0];
+        let aliases = (!aliases.is_empty()).then(|| {
+            quote! {
+                impl #name {
+                    #(#aliases)*
+                }
+            }
+        });
+        quote! {
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, jcers::JceGet, jcers::JcePut, jcers::JceTypeName #(, #derives)*)]
+            #[repr(i32)]
+            pub enum #name {
+                #[default]
+                #first = #first_discriminant,
+                #(#rest = #rest_discriminants,)*
+            }
+
+            #aliases
+
+            impl ::std::convert::From<#name> for i32 {
+                fn from(value: #name) -> i32 {
+                    value as i32
+                }
+            }
+
+            impl ::std::convert::TryFrom<i32> for #name {
+                type Error = i32;
+
+                fn try_from(value: i32) -> ::std::result::Result<Self, i32> {
+                    match value {
+                        #(#discriminants => Ok(#name::#variants),)*
+                        _ => Err(value),
+                    }
+                }
+            }
+        }
+    }
+
+    /// The variant first given the value of `name`, which an alias stands for
+    fn variant<'v>(&self, values: &'v [(&ast::Variant, i32, bool)], name: &'v str) -> &'v str {
+        let value = values
+            .iter()
+            .find(|(v, ..)| v.name.name == name)
+            .map(|(_, value, _)| *value);
+        values
+            .iter()
+            .find(|(_, v, alias)| Some(*v) == value && !alias)
+            .map(|(v, ..)| v.name.name.as_str())
+            .unwrap_or(name)
+    }
+
+    fn gen_const(&self, part: &Part, c: &ast::Const) -> Result<TokenStream, GenError> {
+        let name = ident(&c.name.name);
+        let ty = match &c.ty.kind {
+            TypeKind::String => quote!(&str),
+            TypeKind::Vector(_) | TypeKind::Map(..) => {
+                return Err(part.error(c.ty.span, "a const should be a number, bool or string"))
+            }
+            _ => self.ty(part, &c.ty)?,
+        };
+        let value = self.value(part, &c.ty, &c.value, false)?;
+        Ok(quote!(pub const #name: #ty = #value;))
+    }
+
+    fn ty(&self, part: &Part, ty: &ast::Type) -> Result<TokenStream, GenError> {
+        Ok(match &ty.kind {
+            TypeKind::Void => return Err(part.error(ty.span, "`void` is not a value type")),
+            TypeKind::Bool => quote!(bool),
+            TypeKind::Byte => quote!(u8),
+            TypeKind::Short | TypeKind::UnsignedByte => quote!(i16),
+            TypeKind::Int | TypeKind::UnsignedShort => quote!(i32),
+            TypeKind::Long | TypeKind::UnsignedInt => quote!(i64),
+            TypeKind::Float => quote!(f32),
+            TypeKind::Double => quote!(f64),
+            TypeKind::String => quote!(::std::string::String),
+            TypeKind::Vector(item) if item.kind == TypeKind::Byte => match self.config.bytes {
+                true => quote!(bytes::Bytes),
+                false => quote!(::std::vec::Vec<u8>),
+            },
+            TypeKind::Vector(item) => {
+                let item = self.ty(part, item)?;
+                quote!(::std::vec::Vec<#item>)
+            }
+            TypeKind::Map(k, v) => {
+                let k = self.ty(part, k)?;
+                let v = self.ty(part, v)?;
+                match self.config.map_type {
+                    MapType::HashMap => quote!(::std::collections::HashMap<#k, #v>),
+                    MapType::BTreeMap => quote!(jcers::SortedMap<#k, #v>),
+                }
+            }
+            TypeKind::Named(path) => match self.scope.lookup(part.name(), path) {
+                Some((module, Symbol::Struct)) => item_path(part.name(), module, &path.name().name),
+                // enums are kept as `i32` so unknown values still decode
+                Some((_, Symbol::Enum(_))) => quote!(i32),
+                Some((_, Symbol::Const)) => {
+                    return Err(
+                        part.error(path.span, format!("`{}` is not a type", path.name().name))
+                    )
+                }
+                None => {
+                    return Err(part.error(path.span, format!("unknown type `{}`", path_str(path))))
+                }
+            },
+        })
+    }
+
+    /// Expression of `value` as `ty`, `owned` for a `String` instead of `&str`
+    fn value(
+        &self,
+        part: &Part,
+        ty: &ast::Type,
+        value: &ast::Value,
+        owned: bool,
+    ) -> Result<TokenStream, GenError> {
+        let mismatch = || {
+            part.error(
+                value.span,
+                format!("value does not match the type `{}`", type_str(ty)),
+            )
+        };
+        Ok(match (&ty.kind, &value.kind) {
+            (_, ValueKind::Path(path)) => self.value_path(part, ty, path)?,
+            (TypeKind::Bool, ValueKind::Bool(b)) => quote!(#b),
+            (TypeKind::Bool, ValueKind::Int(i)) => {
+                let b = *i != 0;
+                quote!(#b)
+            }
+            (TypeKind::Float | TypeKind::Double, ValueKind::Int(i)) => float_lit(*i as f64),
+            (TypeKind::Float | TypeKind::Double, ValueKind::Float(f)) => float_lit(*f),
+            (TypeKind::String, ValueKind::String(s)) if owned => quote!(#s.to_owned()),
+            (TypeKind::String, ValueKind::String(s)) => quote!(#s),
+            // a `byte` is an `u8`, negative values are their two's complement
+            (TypeKind::Byte, ValueKind::Int(i)) if (i8::MIN as i64..0).contains(i) => {
+                int_lit(*i as u8 as i64)
+            }
+            (kind, ValueKind::Int(i)) => match int_range(kind, self.scope, part.name()) {
+                Some((min, max)) if (min..=max).contains(i) => int_lit(*i),
+                Some(_) => {
+                    return Err(part.error(
+                        value.span,
+                        format!("`{}` is out of range for `{}`", i, type_str(ty)),
+                    ))
+                }
+                None => return Err(mismatch()),
+            },
+            _ => return Err(mismatch()),
+        })
+    }
+
+    /// A const, or a variant for a number, as in `Color::RED` or `Base::Color::RED`
+    fn value_path(
+        &self,
+        part: &Part,
+        ty: &ast::Type,
+        path: &ast::Path,
+    ) -> Result<TokenStream, GenError> {
+        let unknown = || part.error(path.span, format!("unknown value `{}`", path_str(path)));
+        let name = &path.name().name;
+        let segments = &path.segments;
+        // a const, maybe in another module
+        if segments.len() <= 2 {
+            if let Some((module, Symbol::Const)) = self.scope.lookup(part.name(), path) {
+                return Ok(item_path(part.name(), module, name));
+            }
+        }
+        // a variant, under its enum, or under the enum of the field
+        let (module, enum_name) = match segments.len() {
+            1 => match &ty.kind {
+                TypeKind::Named(ty_path) => (
+                    ty_path.module().map_or(part.name(), |m| m.name.as_str()),
+                    ty_path.name().name.as_str(),
+                ),
+                _ => return Err(unknown()),
+            },
+            2 => (part.name(), segments[0].name.as_str()),
+            3 => (segments[0].name.as_str(), segments[1].name.as_str()),
+            _ => return Err(unknown()),
+        };
+        let values = match self.scope.symbols.get(&(module, enum_name)) {
+            Some(Symbol::Enum(values)) => values,
+            _ => return Err(unknown()),
+        };
+        let (variant, _, alias) = values
+            .iter()
+            .find(|(v, ..)| &v.name.name == name)
+            .ok_or_else(unknown)?;
+        if matches!(
+            ty.kind,
+            TypeKind::String | TypeKind::Bool | TypeKind::Float | TypeKind::Double
+        ) {
+            return Err(part.error(
+                path.span,
+                format!("value does not match the type `{}`", type_str(ty)),
+            ));
+        }
+        let enum_path = item_path(part.name(), module, enum_name);
+        let variant = match alias {
+            true => ident(&variant.name.name.to_uppercase()),
+            false => variant_ident(&variant.name.name),
+        };
+        Ok(quote!(#enum_path::#variant as i32))
+    }
+}
+
+/// Range of integer values of a type, `None` if it is not an integer
+fn int_range(kind: &TypeKind, scope: &Scope, module: &str) -> Option<(i64, i64)> {
+    Some(match kind {
+        TypeKind::Byte => (0, u8::MAX as i64),
+        TypeKind::Short | TypeKind::UnsignedByte => (i16::MIN as i64, i16::MAX as i64),
+        TypeKind::Int | TypeKind::UnsignedShort => (i32::MIN as i64, i32::MAX as i64),
+        TypeKind::Long | TypeKind::UnsignedInt => (i64::MIN, i64::MAX),
+        TypeKind::Named(path) => match scope.lookup(module, path) {
+            Some((_, Symbol::Enum(_))) => (i32::MIN as i64, i32::MAX as i64),
+            _ => return None,
+        },
+        _ => return None,
+    })
+}
+
+fn int_lit(v: i64) -> TokenStream {
+    let lit = Literal::u64_unsuffixed(v.unsigned_abs());
+    match v < 0 {
+        true => quote!(-#lit),
+        false => quote!(#lit),
+    }
+}
+
+fn float_lit(v: f64) -> TokenStream {
+    let lit = Literal::f64_unsuffixed(v.abs());
+    match v < 0.0 {
+        true => quote!(-#lit),
+        false => quote!(#lit),
+    }
+}
+
+/// Path to `name` of `module`, as seen from `current`
+fn item_path(current: &str, module: &str, name: &str) -> TokenStream {
+    let name = ident(name);
+    if current == module {
+        quote!(#name)
+    } else {
+        let module = ident(&snake(module));
+        quote!(super::#module::#name)
+    }
+}
+
+fn path_str(path: &ast::Path) -> String {
+    path.segments
+        .iter()
+        .map(|s| s.name.as_str())
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// The type as written in the IDL
+fn type_str(ty: &ast::Type) -> String {
+    match &ty.kind {
+        TypeKind::Void => "void".to_owned(),
+        TypeKind::Bool => "bool".to_owned(),
+        TypeKind::Byte => "byte".to_owned(),
+        TypeKind::Short => "short".to_owned(),
+        TypeKind::Int => "int".to_owned(),
+        TypeKind::Long => "long".to_owned(),
+        TypeKind::Float => "float".to_owned(),
+        TypeKind::Double => "double".to_owned(),
+        TypeKind::String => "string".to_owned(),
+        TypeKind::UnsignedByte => "unsigned byte".to_owned(),
+        TypeKind::UnsignedShort => "unsigned short".to_owned(),
+        TypeKind::UnsignedInt => "unsigned int".to_owned(),
+        TypeKind::Vector(item) => format!("vector<{}>", type_str(item)),
+        TypeKind::Map(k, v) => format!("map<{}, {}>", type_str(k), type_str(v)),
+        TypeKind::Named(path) => path_str(path),
+    }
+}
+
+const KEYWORDS: [&str; 51] = [
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+/// Identifier for `name`, raw if it is a keyword
+pub(crate) fn ident(name: &str) -> Ident {
+    match name {
+        // these can not be raw identifiers
+        "self" | "Self" | "super" | "crate" => quote::format_ident!("{}_", name),
+        _ if KEYWORDS.contains(&name) => Ident::new_raw(name, proc_macro2::Span::call_site()),
+        _ => Ident::new(name, proc_macro2::Span::call_site()),
+    }
+}
+
+fn variant_ident(name: &str) -> Ident {
+    ident(&camel(name))
+}
+
+/// `snake_case` of a `camelCase`, `CamelCase` or `SCREAMING_CASE` name
+pub(crate) fn snake(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut s = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|c| c.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_lower)
+            {
+                s.push('_');
+            }
+        }
+        s.push(c.to_ascii_lowercase());
+    }
+    s
+}
+
+/// `CamelCase` of a `SCREAMING_CASE` or `camelCase` name
+fn camel(name: &str) -> String {
+    let mut s = String::new();
+    for word in name.split('_').filter(|w| !w.is_empty()) {
+        let upper = !word.chars().any(|c| c.is_ascii_lowercase());
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            s.push(first.to_ascii_uppercase());
+        }
+        match upper {
+            true => s.extend(chars.map(|c| c.to_ascii_lowercase())),
+            false => s.extend(chars),
+        }
+    }
+    if !s.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        s.insert(0, '_');
+    }
+    s
+}
@@ -0,0 +1,332 @@
+//! Generate jcers structs from jce / tars IDL files
+//!
+//! Meant to be called from `build.rs`, in the manner of `prost-build`:
+//!
+//! ```no_run
+//! // build.rs
+//! jcers_build::compile(&["idl/sample.jce"], &["idl"]).unwrap();
+//! ```
+//!
+//! Each IDL module is written to `<module>.rs` in `OUT_DIR`, with the module
+//! name in snake case. Include the files as sibling modules, references to
+//! other modules are generated as `super::<module>::Name`:
+//!
+//! ```ignore
+//! pub mod sample {
+//!     include!(concat!(env!("OUT_DIR"), "/sample.rs"));
+//! }
+//! ```
+//!
+//! The generated code derives `jcers::JceGet`, `jcers::JcePut` and
+//! `jcers::JceTypeName`, named `Module.Struct`, so the crate needs `jcers`
+//! with the `derive` feature and `bytes`. Structs get a
+//! `Default` from the IDL defaults, enums become `#[repr(i32)]` enums and
+//! fields of enum type are kept as `i32`. Interfaces and keys are skipped.
+
+mod gen;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use jcers_idl::Span;
+use proc_macro2::TokenStream;
+
+/// Rust type of the IDL `map<K, V>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MapType {
+    #[default]
+    HashMap,
+    /// `jcers::SortedMap`, a map on the wire backed by a `BTreeMap`
+    BTreeMap,
+}
+
+/// Code generation options
+#[derive(Debug, Clone)]
+pub struct Config {
+    map_type: MapType,
+    bytes: bool,
+    derives: Vec<String>,
+    out_dir: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            map_type: MapType::HashMap,
+            bytes: true,
+            derives: vec![],
+            out_dir: None,
+        }
+    }
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rust type of `map<K, V>`, `HashMap` by default
+    pub fn map_type(&mut self, map_type: MapType) -> &mut Self {
+        self.map_type = map_type;
+        self
+    }
+
+    /// Generate `vector<byte>` as `bytes::Bytes` (the default) or `Vec<u8>`
+    ///
+    /// `Bytes` is written as the SimpleList tars uses for `vector<byte>`, a
+    /// `Vec<u8>` as a List of bytes.
+    pub fn bytes(&mut self, bytes: bool) -> &mut Self {
+        self.bytes = bytes;
+        self
+    }
+
+    /// Derive another trait on the generated structs and enums, as `serde::Serialize`
+    pub fn derive(&mut self, path: impl Into<String>) -> &mut Self {
+        self.derives.push(path.into());
+        self
+    }
+
+    /// Write to this directory instead of `OUT_DIR`
+    pub fn out_dir(&mut self, out_dir: impl Into<PathBuf>) -> &mut Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// Generate code for `files` and everything they include, and write a
+    /// `<module>.rs` for each IDL module
+    ///
+    /// `#include` is looked up next to the including file, then in `includes`.
+    pub fn compile(
+        &self,
+        files: &[impl AsRef<Path>],
+        includes: &[impl AsRef<Path>],
+    ) -> io::Result<()> {
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "OUT_DIR is not set"))?,
+        };
+        let sources = load_files(files, includes)?;
+        for source in &sources {
+            if let Some(path) = &source.path {
+                println!("cargo:rerun-if-changed={}", path.display());
+            }
+        }
+        for (module, code) in self.generate_sources(&sources)? {
+            fs::write(out_dir.join(format!("{}.rs", module)), code)?;
+        }
+        Ok(())
+    }
+
+    /// Like [`Config::compile`], returning the code of each module by file
+    /// name instead of writing it
+    pub fn generate(
+        &self,
+        files: &[impl AsRef<Path>],
+        includes: &[impl AsRef<Path>],
+    ) -> io::Result<BTreeMap<String, String>> {
+        self.generate_sources(&load_files(files, includes)?)
+    }
+
+    fn generate_sources(&self, sources: &[Source]) -> io::Result<BTreeMap<String, String>> {
+        let invalid = |e: String| io::Error::new(io::ErrorKind::InvalidData, e);
+        let derives = gen::derives(self).map_err(invalid)?;
+        let modules = gen::modules(self, derives, sources)
+            .map_err(|e| invalid(sources[e.source].render(&e.error)))?;
+        Ok(modules
+            .into_iter()
+            .map(|(module, tokens)| (module, gen::format(tokens)))
+            .collect())
+    }
+
+    /// Code of `src` and the files it includes, as `pub mod`s, for the
+    /// `jce_idl!` and `jce_include!` macros
+    ///
+    /// `path` is the file `src` is read from, if any. Includes are looked up
+    /// in `dir`, which is the directory of `path` for a file.
+    #[doc(hidden)]
+    pub fn expand(
+        &self,
+        src: String,
+        path: Option<PathBuf>,
+        dir: &Path,
+    ) -> Result<(TokenStream, Vec<PathBuf>), ExpandError> {
+        let name = path
+            .as_ref()
+            .map_or_else(|| "jce_idl!".to_owned(), |p| p.display().to_string());
+        let mut sources = vec![];
+        add_source(name, path, dir, src, &[], &mut sources).map_err(|e| match e {
+            LoadError::Io(e) => ExpandError::new(e.to_string()),
+            LoadError::Idl(source, e) => ExpandError::at(&sources, source, e),
+        })?;
+        let derives = gen::derives(self).map_err(ExpandError::new)?;
+        let modules = gen::modules(self, derives, &sources)
+            .map_err(|e| ExpandError::at(&sources, e.source, e.error))?;
+        let modules = modules.into_iter().map(|(module, tokens)| {
+            let module = gen::ident(&module);
+            quote::quote! {
+                pub mod #module {
+                    #tokens
+                }
+            }
+        });
+        let paths = sources.into_iter().filter_map(|s| s.path).collect();
+        Ok((modules.collect(), paths))
+    }
+}
+
+/// Error of [`Config::expand`]
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ExpandError {
+    /// the error with the source line it is at
+    pub rendered: String,
+    /// the message and span, if the error is in the given source
+    pub at: Option<(String, Span)>,
+}
+
+impl ExpandError {
+    fn new(message: String) -> Self {
+        ExpandError {
+            rendered: message,
+            at: None,
+        }
+    }
+
+    fn at(sources: &[Source], source: usize, error: jcers_idl::Error) -> Self {
+        ExpandError {
+            rendered: sources[source].render(&error),
+            at: (source == 0).then_some((error.message, error.span)),
+        }
+    }
+}
+
+/// Compile with the default [`Config`]
+pub fn compile(files: &[impl AsRef<Path>], includes: &[impl AsRef<Path>]) -> io::Result<()> {
+    Config::new().compile(files, includes)
+}
+
+/// A parsed IDL file
+struct Source {
+    /// position in the sources, for errors
+    index: usize,
+    /// name in errors
+    name: String,
+    /// canonical path of the file, `None` for a macro input
+    path: Option<PathBuf>,
+    src: String,
+    file: jcers_idl::ast::File,
+}
+
+impl Source {
+    fn render(&self, error: &jcers_idl::Error) -> String {
+        error.render(&self.src, &self.name)
+    }
+}
+
+enum LoadError {
+    Io(io::Error),
+    /// parse error, or an include not found, in a source
+    Idl(usize, jcers_idl::Error),
+}
+
+/// Parse `files` and the files they include, once each
+fn load_files(
+    files: &[impl AsRef<Path>],
+    includes: &[impl AsRef<Path>],
+) -> io::Result<Vec<Source>> {
+    let includes: Vec<&Path> = includes.iter().map(AsRef::as_ref).collect();
+    let mut sources = vec![];
+    for file in files {
+        load(file.as_ref(), &includes, &mut sources).map_err(|e| match e {
+            LoadError::Io(e) => e,
+            LoadError::Idl(source, e) => {
+                io::Error::new(io::ErrorKind::InvalidData, sources[source].render(&e))
+            }
+        })?;
+    }
+    Ok(sources)
+}
+
+fn load(path: &Path, includes: &[&Path], sources: &mut Vec<Source>) -> Result<(), LoadError> {
+    let path = fs::canonicalize(path).map_err(|e| {
+        LoadError::Io(io::Error::new(
+            e.kind(),
+            format!("{}: {}", path.display(), e),
+        ))
+    })?;
+    if sources.iter().any(|s| s.path.as_ref() == Some(&path)) {
+        return Ok(());
+    }
+    let src = fs::read_to_string(&path).map_err(LoadError::Io)?;
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let name = path.display().to_string();
+    add_source(name, Some(path), &dir, src, includes, sources)
+}
+
+/// Parse `src` into `sources`, then the files it includes, looked up in
+/// `dir` and then `includes`
+fn add_source(
+    name: String,
+    path: Option<PathBuf>,
+    dir: &Path,
+    src: String,
+    includes: &[&Path],
+    sources: &mut Vec<Source>,
+) -> Result<(), LoadError> {
+    let index = sources.len();
+    let file = match jcers_idl::parse(&src) {
+        Ok(file) => file,
+        Err(e) => {
+            // keep the source to render the error with
+            sources.push(Source {
+                index,
+                name,
+                path,
+                src,
+                file: jcers_idl::ast::File {
+                    includes: vec![],
+                    modules: vec![],
+                },
+            });
+            return Err(LoadError::Idl(index, e));
+        }
+    };
+    let source = Source {
+        index,
+        name,
+        path,
+        src,
+        file,
+    };
+    let mut found = vec![];
+    let mut missing = None;
+    for include in &source.file.includes {
+        let candidate = std::iter::once(dir)
+            .chain(includes.iter().copied())
+            .map(|dir| dir.join(&include.path))
+            .find(|p| p.is_file());
+        match candidate {
+            Some(p) => found.push(p),
+            None => {
+                missing = Some(jcers_idl::Error::new(
+                    &source.src,
+                    include.span,
+                    format!("include `{}` is not found", include.path),
+                ));
+                break;
+            }
+        }
+    }
+    sources.push(source);
+    if let Some(e) = missing {
+        return Err(LoadError::Idl(index, e));
+    }
+    for p in found {
+        load(&p, includes, sources)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,66 @@
+// This file is @generated by jcers_build, do not edit.
+
+pub const VERSION: i64 = -1;
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Default,
+    jcers::JceGet,
+    jcers::JcePut,
+    jcers::JceTypeName
+)]
+#[repr(i32)]
+pub enum Kind {
+    #[default]
+    Normal = 0,
+    Special = 5,
+}
+impl ::std::convert::From<Kind> for i32 {
+    fn from(value: Kind) -> i32 {
+        value as i32
+    }
+}
+impl ::std::convert::TryFrom<i32> for Kind {
+    type Error = i32;
+    fn try_from(value: i32) -> ::std::result::Result<Self, i32> {
+        match value {
+            0 => Ok(Kind::Normal),
+            5 => Ok(Kind::Special),
+            _ => Err(value),
+        }
+    }
+}
+#[derive(Debug, Clone, PartialEq, jcers::JceGet, jcers::JcePut, jcers::JceTypeName)]
+#[jce(name = "Base.Item")]
+pub struct Item {
+    #[jce(0, require)]
+    pub name: ::std::string::String,
+    #[jce(1, default = Kind::Special as i32)]
+    pub kind: i32,
+    #[jce(2, default = -0.5)]
+    pub weight: f64,
+    #[jce(3, optional)]
+    pub tags: ::std::vec::Vec<::std::string::String>,
+    #[jce(4, default = 7)]
+    pub r#type: u8,
+    #[jce(5, default = 255)]
+    pub flags: u8,
+}
+impl ::std::default::Default for Item {
+    fn default() -> Self {
+        Item {
+            name: ::std::default::Default::default(),
+            kind: Kind::Special as i32,
+            weight: -0.5,
+            tags: ::std::default::Default::default(),
+            r#type: 7,
+            flags: 255,
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// This file is @generated by jcers_build, do not edit.
+
+pub const MAX_LEN: i32 = 256;
+pub const NAME: &str = "sample\n";
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    Default,
+    jcers::JceGet,
+    jcers::JcePut,
+    jcers::JceTypeName
+)]
+#[repr(i32)]
+pub enum Color {
+    #[default]
+    Red = 1,
+    Green = 2,
+    Blue = -2,
+}
+impl Color {
+    pub const DEFAULT: Color = Color::Red;
+}
+impl ::std::convert::From<Color> for i32 {
+    fn from(value: Color) -> i32 {
+        value as i32
+    }
+}
+impl ::std::convert::TryFrom<i32> for Color {
+    type Error = i32;
+    fn try_from(value: i32) -> ::std::result::Result<Self, i32> {
+        match value {
+            1 => Ok(Color::Red),
+            2 => Ok(Color::Green),
+            -2 => Ok(Color::Blue),
+            _ => Err(value),
+        }
+    }
+}
+#[derive(Debug, Clone, PartialEq, jcers::JceGet, jcers::JcePut, jcers::JceTypeName)]
+#[jce(name = "Sample.Request")]
+pub struct Request {
+    #[jce(0, require)]
+    pub id: i64,
+    #[jce(1, default = "anonymous".to_owned())]
+    pub name: ::std::string::String,
+    #[jce(2, optional)]
+    pub payload: bytes::Bytes,
+    #[jce(3, optional)]
+    pub items: ::std::collections::HashMap<
+        ::std::string::String,
+        ::std::vec::Vec<super::base::Item>,
+    >,
+    #[jce(4, default = 10)]
+    pub count: i64,
+    #[jce(5, default = Color::Green as i32)]
+    pub color: i32,
+    #[jce(6, default = 1.5)]
+    pub ratio: f32,
+    #[jce(7, default = true)]
+    pub enabled: bool,
+}
+impl ::std::default::Default for Request {
+    fn default() -> Self {
+        Request {
+            id: ::std::default::Default::default(),
+            name: "anonymous".to_owned(),
+            payload: ::std::default::Default::default(),
+            items: ::std::default::Default::default(),
+            count: 10,
+            color: Color::Green as i32,
+            ratio: 1.5,
+            enabled: true,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use std::fs;
+use std::path::PathBuf;
+
+use jcers_build::{Config, MapType};
+
+const NO_INCLUDES: &[&str] = &[];
+
+/// Write `src` to a file of its own in the temp dir
+fn idl_file(name: &str, src: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("jcers_build_{}", std::process::id()));
+    let path = dir.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, src).unwrap();
+    path
+}
+
+/// The code in tests/data/out is compiled by tests/generated.rs, it is
+/// rewritten when `JCERS_BLESS` is set
+#[test]
+fn test_generate_sample() {
+    let out = Config::new()
+        .generate(&["tests/data/sample.jce"], NO_INCLUDES)
+        .unwrap();
+    assert_eq!(out.keys().collect::<Vec<_>>(), ["base", "sample"]);
+    for (module, code) in out {
+        let path = format!("tests/data/out/{}.rs", module);
+        if std::env::var_os("JCERS_BLESS").is_some() {
+            fs::write(&path, &code).unwrap();
+        }
+        let expected = fs::read_to_string(&path).unwrap().replace("\r\n", "\n");
+        assert_eq!(code, expected, "{} is outdated", path);
+    }
+}
+
+#[test]
+fn test_generate_options() {
+    let out = Config::new()
+        .map_type(MapType::BTreeMap)
+        .bytes(false)
+        .derive("serde::Serialize")
+        .generate(&["tests/data/sample.jce"], NO_INCLUDES)
+        .unwrap();
+    let sample = &out["sample"];
+    assert!(sample.contains("pub payload: ::std::vec::Vec<u8>,"));
+    assert!(sample.contains("jcers::SortedMap<"));
+    // on the enum and the struct
+    assert_eq!(out["base"].matches("serde::Serialize").count(), 2);
+
+    let dir = std::env::temp_dir().join(format!("jcers_build_out_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    Config::new()
+        .out_dir(&dir)
+        .compile(&["tests/data/sample.jce"], NO_INCLUDES)
+        .unwrap();
+    assert!(dir.join("sample.rs").is_file());
+    assert!(dir.join("base.rs").is_file());
+}
+
+#[test]
+fn test_generate_includes() {
+    let base = idl_file(
+        "include/base.jce",
+        &fs::read_to_string("tests/data/base.jce").unwrap(),
+    );
+    let main = idl_file(
+        "main.jce",
+        "#include \"base.jce\"\nmodule Main { struct A { 0 require Base::Item item; }; };",
+    );
+    let include = base.parent().unwrap();
+    let out = Config::new().generate(&[&main], &[include]).unwrap();
+    assert!(out["main"].contains("pub item: super::base::Item,"));
+
+    let e = Config::new().generate(&[&main], NO_INCLUDES).unwrap_err();
+    assert!(
+        e.to_string().contains("include `base.jce` is not found"),
+        "{}",
+        e
+    );
+}
+
+#[test]
+fn test_generate_errors() {
+    let cases = [
+        (
+            "module M { struct A { 0 require Other b; }; };",
+            "unknown type `Other`",
+        ),
+        (
+            "module M { const int C = 1; struct A { 0 require C b; }; };",
+            "`C` is not a type",
+        ),
+        (
+            "module M { struct A { 0 optional byte b = 256; }; };",
+            "`256` is out of range for `byte`",
+        ),
+        (
+            "module M { struct A { 0 optional byte b = -129; }; };",
+            "`-129` is out of range for `byte`",
+        ),
+        (
+            "module M { struct A { 0 optional string b = 1; }; };",
+            "value does not match the type `string`",
+        ),
+        (
+            "module M { enum E { A }; struct A { 0 optional E e = E::B; }; };",
+            "unknown value `E::B`",
+        ),
+        (
+            "module M { enum E { A = B, B }; };",
+            "`B` is not an earlier variant of `E`",
+        ),
+        (
+            "module M { const vector<int> C = 1; };",
+            "a const should be a number, bool or string",
+        ),
+    ];
+    for (i, (src, message)) in cases.into_iter().enumerate() {
+        let path = idl_file(&format!("error{}.jce", i), src);
+        let e = Config::new().generate(&[&path], NO_INCLUDES).unwrap_err();
+        assert!(e.to_string().contains(message), "{}: {}", src, e);
+    }
+
+    // the same module in two files may not define an item twice
+    let a = idl_file(
+        "twice_a.jce",
+        "module M { struct A { 0 require int a; }; };",
+    );
+    let b = idl_file("twice_b.jce", "module M { enum A { X }; };");
+    let e = Config::new().generate(&[&a, &b], NO_INCLUDES).unwrap_err();
+    assert!(e
+        .to_string()
+        .contains("`A` is already defined in module `M`"));
+    assert!(e.to_string().contains("twice_b.jce:1:"), "{}", e);
+}
+
+#[test]
+fn test_expand() {
+    let dir = std::path::Path::new("tests/data");
+    let src = "#include \"base.jce\"\nmodule M { struct A { 0 require Base::Item a; }; };";
+    let (tokens, paths) = Config::new().expand(src.to_owned(), None, dir).unwrap();
+    let tokens = tokens.to_string();
+    assert!(tokens.contains("pub mod m {"), "{}", tokens);
+    assert!(tokens.contains("pub mod base {"), "{}", tokens);
+    assert!(paths[0].ends_with("tests/data/base.jce"));
+
+    // errors in the given source keep their span, others are only rendered
+    let src = "module M { struct A { 0 require Foo a; }; };";
+    let e = Config::new().expand(src.to_owned(), None, dir).unwrap_err();
+    let (message, span) = e.at.unwrap();
+    assert_eq!(message, "unknown type `Foo`");
+    assert_eq!(&src[span.start..span.end], "Foo");
+    assert!(e.rendered.contains("--> jce_idl!:1:33"), "{}", e.rendered);
+
+    let src = "#include \"broken.jce\"\nmodule M {};";
+    let dir = idl_file("broken.jce", "module B { struct };");
+    let e = Config::new()
+        .expand(src.to_owned(), None, dir.parent().unwrap())
+        .unwrap_err();
+    assert!(e.at.is_none());
+    assert!(e.rendered.contains("broken.jce:1:19"), "{}", e.rendered);
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+// the generated modules refer to each other as siblings
+mod base {
+    include!("data/out/base.rs");
+}
+
+mod sample {
+    include!("data/out/sample.rs");
+}
+
+use base::{Item, Kind};
+use sample::{Color, Request};
+
+#[test]
+fn test_generated_defaults() {
+    let request = Request::default();
+    assert_eq!(request.name, "anonymous");
+    assert_eq!(request.count, 10);
+    assert_eq!(request.color, Color::Green as i32);
+    assert_eq!(request.ratio, 1.5);
+    assert!(request.enabled);
+    assert_eq!(Item::default().kind, i32::from(Kind::Special));
+    assert_eq!(Item::default().weight, -0.5);
+    // -1 as a byte
+    assert_eq!(Item::default().flags, 0xff);
+    assert_eq!(sample::MAX_LEN, 256);
+    assert_eq!(sample::NAME, "sample\n");
+    assert_eq!(base::VERSION, -1);
+
+    use jcers::JceTypeName as _;
+    assert_eq!(Item::type_name(), "Base.Item");
+    assert_eq!(<Vec<Kind>>::type_name(), "list<int32>");
+}
+
+#[test]
+fn test_generated_enums() {
+    assert_eq!(Color::DEFAULT, Color::Red);
+    assert_eq!(Color::default(), Color::Red);
+    assert_eq!(Color::try_from(-2), Ok(Color::Blue));
+    assert_eq!(Color::try_from(3), Err(3));
+    assert_eq!(Kind::try_from(5), Ok(Kind::Special));
+}
+
+#[test]
+fn test_generated_round_trip() {
+    use jcers::JcePut as _;
+
+    let request = Request {
+        id: 1,
+        payload: Bytes::from_static(b"\x01\x02"),
+        items: HashMap::from([(
+            "a".to_owned(),
+            vec![Item {
+                name: "b".to_owned(),
+                tags: vec!["c".to_owned()],
+                r#type: 3,
+                ..Default::default()
+            }],
+        )]),
+        color: Color::Blue as i32,
+        ..Default::default()
+    };
+    let decoded: Request = jcers::from_buf(&mut request.freeze()).unwrap();
+    assert_eq!(decoded, request);
+}
+
+#[test]
+fn test_generated_missing_fields() {
+    // { 0: 1 }, optional fields take their IDL defaults
+    let request: Request = jcers::from_buf(&mut Bytes::from_static(&[0x00, 0x01])).unwrap();
+    assert_eq!(
+        request,
+        Request {
+            id: 1,
+            ..Default::default()
+        }
+    );
+    // `id` is required
+    let e = jcers::from_buf::<_, Request>(&mut Bytes::from_static(&[0x40, 0x01])).unwrap_err();
+    assert_eq!(e.kind(), &jcers::JceError::TagNotFound(0));
+}
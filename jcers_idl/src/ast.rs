@@ -0,0 +1,180 @@
+use super::error::Span;
+
+/// A parsed `.jce` / `.tars` file
+#[derive(Debug, Clone, PartialEq)]
+pub struct File {
+    pub includes: Vec<Include>,
+    pub modules: Vec<Module>,
+}
+
+/// `#include "path"`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Include {
+    pub path: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ident {
+    pub name: String,
+    pub span: Span,
+}
+
+/// Name of a type or value, maybe in another module as `Module::Name`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    pub segments: Vec<Ident>,
+    pub span: Span,
+}
+
+impl Path {
+    /// The last segment
+    pub fn name(&self) -> &Ident {
+        self.segments.last().unwrap()
+    }
+
+    /// The module, if given
+    pub fn module(&self) -> Option<&Ident> {
+        self.segments
+            .len()
+            .checked_sub(2)
+            .map(|i| &self.segments[i])
+    }
+}
+
+/// `module Name { ... };`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Module {
+    pub name: Ident,
+    pub items: Vec<Item>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Item {
+    Struct(Struct),
+    Enum(Enum),
+    Const(Const),
+    Key(Key),
+    Interface(Interface),
+}
+
+/// `struct Name { ... };`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Struct {
+    pub name: Ident,
+    pub fields: Vec<Field>,
+    pub span: Span,
+}
+
+/// `0 require int name = 1;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub tag: u8,
+    pub require: bool,
+    pub ty: Type,
+    pub name: Ident,
+    pub default: Option<Value>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Type {
+    pub kind: TypeKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeKind {
+    Void,
+    Bool,
+    Byte,
+    Short,
+    Int,
+    Long,
+    Float,
+    Double,
+    String,
+    UnsignedByte,
+    UnsignedShort,
+    UnsignedInt,
+    Vector(Box<Type>),
+    Map(Box<Type>, Box<Type>),
+    /// struct or enum, maybe from another module
+    Named(Path),
+}
+
+/// `enum Name { A = 1, B, };`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum {
+    pub name: Ident,
+    pub variants: Vec<Variant>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    pub name: Ident,
+    /// an integer or another variant, the previous value plus one if absent
+    pub value: Option<Value>,
+    pub span: Span,
+}
+
+/// `const int NAME = 1;`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Const {
+    pub ty: Type,
+    pub name: Ident,
+    pub value: Value,
+    pub span: Span,
+}
+
+/// `key[Struct, field, ...];`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Key {
+    pub name: Ident,
+    pub fields: Vec<Ident>,
+    pub span: Span,
+}
+
+/// `interface Name { ... };`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interface {
+    pub name: Ident,
+    pub operations: Vec<Operation>,
+    pub span: Span,
+}
+
+/// `int call(int a, out string b);`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Operation {
+    pub ret: Type,
+    pub name: Ident,
+    pub params: Vec<Param>,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Param {
+    pub out: bool,
+    pub routekey: bool,
+    pub ty: Type,
+    pub name: Ident,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Value {
+    pub kind: ValueKind,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueKind {
+    Int(i64),
+    Float(f64),
+    String(String),
+    Bool(bool),
+    /// an enum variant or a const
+    Path(Path),
+}
@@ -0,0 +1,84 @@
+use std::fmt::{self, Display};
+
+/// Byte range in the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// Span from the start of `self` to the end of `other`
+    pub fn to(self, other: Span) -> Self {
+        Span::new(self.start, other.end)
+    }
+}
+
+/// Parse error at a span of the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub message: String,
+    pub span: Span,
+    /// 1 based line of the span start
+    pub line: usize,
+    /// 1 based column of the span start, in chars
+    pub column: usize,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// Error at `span` of `src`, for checks done on the AST
+    pub fn new(src: &str, span: Span, message: impl Into<String>) -> Self {
+        let before = &src[..span.start.min(src.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Error {
+            message: message.into(),
+            span,
+            line,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// Render the error with the source line and a marker under the span
+    ///
+    /// ```text
+    /// error: expected `;`, found `}`
+    ///  --> test.jce:3:5
+    ///   |
+    /// 3 |     }
+    ///   |     ^
+    /// ```
+    pub fn render(&self, src: &str, name: &str) -> String {
+        let text = src.lines().nth(self.line - 1).unwrap_or_default();
+        let text = text.strip_suffix('\r').unwrap_or(text);
+        let line_no = self.line.to_string();
+        let pad = " ".repeat(line_no.len());
+        let rest = text.chars().count().saturating_sub(self.column - 1);
+        let width = src
+            .get(self.span.start..self.span.end)
+            .map_or(1, |s| s.chars().count())
+            .clamp(1, rest.max(1));
+        format!(
+            "error: {}\n{pad}--> {name}:{}:{}\n{pad} |\n{line_no} | {text}\n{pad} | {}{}\n",
+            self.message,
+            self.line,
+            self.column,
+            " ".repeat(self.column - 1),
+            "^".repeat(width),
+        )
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for Error {}
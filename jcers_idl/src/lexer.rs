@@ -0,0 +1,189 @@
+use super::error::{Error, Result, Span};
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TokenKind {
+    Ident(String),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    /// one of `{}<>()[];,=#` or `::`
+    Punct(&'static str),
+    Eof,
+}
+
+impl TokenKind {
+    /// How the token is named in error messages
+    pub(crate) fn describe(&self) -> String {
+        match self {
+            TokenKind::Ident(s) => format!("`{}`", s),
+            TokenKind::Int(i) => format!("`{}`", i),
+            TokenKind::Float(f) => format!("`{}`", f),
+            TokenKind::Str(s) => format!("{:?}", s),
+            TokenKind::Punct(p) => format!("`{}`", p),
+            TokenKind::Eof => "end of file".to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+const PUNCTS: [&str; 13] = [
+    "::", "{", "}", "<", ">", "(", ")", "[", "]", ";", ",", "=", "#",
+];
+
+/// Split the source into tokens, skipping whitespace and comments
+pub(crate) fn tokenize(src: &str) -> Result<Vec<Token>> {
+    let bytes = src.as_bytes();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+        if c.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        if src[i..].starts_with("//") {
+            i = src[i..].find('\n').map_or(bytes.len(), |n| i + n);
+            continue;
+        }
+        if src[i..].starts_with("/*") {
+            match src[i + 2..].find("*/") {
+                Some(n) => i += n + 4,
+                None => {
+                    return Err(Error::new(
+                        src,
+                        Span::new(i, i + 2),
+                        "unterminated block comment",
+                    ))
+                }
+            }
+            continue;
+        }
+        let kind = if c.is_ascii_alphabetic() || c == b'_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            TokenKind::Ident(src[start..i].to_owned())
+        } else if c.is_ascii_digit()
+            || (c == b'-' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit))
+        {
+            let (kind, end) = number(src, start)?;
+            i = end;
+            kind
+        } else if c == b'"' {
+            let (s, end) = string(src, start)?;
+            i = end;
+            TokenKind::Str(s)
+        } else if let Some(p) = PUNCTS.iter().find(|p| src[i..].starts_with(**p)) {
+            i += p.len();
+            TokenKind::Punct(p)
+        } else {
+            let ch = src[i..].chars().next().unwrap();
+            return Err(Error::new(
+                src,
+                Span::new(i, i + ch.len_utf8()),
+                format!("unexpected character `{}`", ch),
+            ));
+        };
+        tokens.push(Token {
+            kind,
+            span: Span::new(start, i),
+        });
+    }
+    tokens.push(Token {
+        kind: TokenKind::Eof,
+        span: Span::new(bytes.len(), bytes.len()),
+    });
+    Ok(tokens)
+}
+
+/// Integer in decimal or hex, or a float, starting at `start`
+fn number(src: &str, start: usize) -> Result<(TokenKind, usize)> {
+    let bytes = src.as_bytes();
+    let mut i = start;
+    if bytes[i] == b'-' {
+        i += 1;
+    }
+    let hex = src[i..].starts_with("0x") || src[i..].starts_with("0X");
+    if hex {
+        i += 2;
+    }
+    let mut float = false;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c.is_ascii_digit() || (hex && c.is_ascii_hexdigit()) {
+            i += 1;
+        } else if !hex && (c == b'.' || c == b'e' || c == b'E') {
+            float = true;
+            i += 1;
+            if (c == b'e' || c == b'E') && matches!(bytes.get(i), Some(b'-' | b'+')) {
+                i += 1;
+            }
+        } else {
+            break;
+        }
+    }
+    let text = &src[start..i];
+    // a float may end with `f`, as in `1.5f`
+    if float && matches!(bytes.get(i), Some(b'f' | b'F')) {
+        i += 1;
+    }
+    let span = Span::new(start, i);
+    let kind = if float {
+        text.parse()
+            .map(TokenKind::Float)
+            .map_err(|_| Error::new(src, span, format!("invalid float `{}`", text)))?
+    } else if hex {
+        let (neg, digits) = match text.strip_prefix('-') {
+            Some(rest) => (true, &rest[2..]),
+            None => (false, &text[2..]),
+        };
+        i64::from_str_radix(digits, 16)
+            .map(|v| TokenKind::Int(if neg { -v } else { v }))
+            .map_err(|_| Error::new(src, span, format!("invalid integer `{}`", text)))?
+    } else {
+        text.parse()
+            .map(TokenKind::Int)
+            .map_err(|_| Error::new(src, span, format!("invalid integer `{}`", text)))?
+    };
+    Ok((kind, i))
+}
+
+/// String literal starting at the quote at `start`
+fn string(src: &str, start: usize) -> Result<(String, usize)> {
+    let mut s = String::new();
+    let mut chars = src[start + 1..].char_indices();
+    while let Some((n, c)) = chars.next() {
+        match c {
+            '"' => return Ok((s, start + 1 + n + 1)),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => s.push('\n'),
+                Some((_, 't')) => s.push('\t'),
+                Some((_, 'r')) => s.push('\r'),
+                Some((_, '0')) => s.push('\0'),
+                Some((_, c @ ('"' | '\\' | '\''))) => s.push(c),
+                Some((m, c)) => {
+                    let at = start + 1 + m;
+                    return Err(Error::new(
+                        src,
+                        Span::new(at - 1, at + c.len_utf8()),
+                        format!("unknown escape `\\{}`", c),
+                    ));
+                }
+                None => break,
+            },
+            '\n' => break,
+            c => s.push(c),
+        }
+    }
+    Err(Error::new(
+        src,
+        Span::new(start, start + 1),
+        "unterminated string",
+    ))
+}
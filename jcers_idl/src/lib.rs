@@ -0,0 +1,12 @@
+//! Parser of the jce / tars IDL
+//!
+//! Parses `.jce` and `.tars` files into the typed AST in [`ast`]. Errors
+//! carry the span they were found at, see [`Error::render`].
+
+pub mod ast;
+mod error;
+mod lexer;
+mod parser;
+
+pub use error::{Error, Result, Span};
+pub use parser::parse;
@@ -0,0 +1,517 @@
+use std::collections::HashMap;
+
+use super::ast::*;
+use super::error::{Error, Result, Span};
+use super::lexer::{tokenize, Token, TokenKind};
+
+/// Parse the source of a `.jce` / `.tars` file
+///
+/// ## Example
+/// ```rust
+/// let file = jcers_idl::parse("module M { struct S { 0 require int a; }; };").unwrap();
+/// assert_eq!(file.modules[0].name.name, "M");
+/// ```
+pub fn parse(src: &str) -> Result<File> {
+    Parser {
+        src,
+        tokens: tokenize(src)?,
+        pos: 0,
+    }
+    .file()
+}
+
+struct Parser<'a> {
+    src: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if token.kind != TokenKind::Eof {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// Span of the last token taken
+    fn last_span(&self) -> Span {
+        self.tokens[self.pos.saturating_sub(1)].span
+    }
+
+    fn error(&self, span: Span, message: impl Into<String>) -> Error {
+        Error::new(self.src, span, message)
+    }
+
+    /// Error for the next token, which is not one of `expected`
+    fn unexpected<T>(&self, expected: &str) -> Result<T> {
+        let token = self.peek();
+        Err(self.error(
+            token.span,
+            format!("expected {}, found {}", expected, token.kind.describe()),
+        ))
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(&self.peek().kind, TokenKind::Punct(p) if *p == punct)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().kind, TokenKind::Ident(s) if s == keyword)
+    }
+
+    fn eat_punct(&mut self, punct: &str) -> bool {
+        let is = self.is_punct(punct);
+        if is {
+            self.next();
+        }
+        is
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let is = self.is_keyword(keyword);
+        if is {
+            self.next();
+        }
+        is
+    }
+
+    fn expect_punct(&mut self, punct: &str) -> Result<Span> {
+        if self.is_punct(punct) {
+            Ok(self.next().span)
+        } else {
+            self.unexpected(&format!("`{}`", punct))
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<Span> {
+        if self.is_keyword(keyword) {
+            Ok(self.next().span)
+        } else {
+            self.unexpected(&format!("`{}`", keyword))
+        }
+    }
+
+    fn ident(&mut self) -> Result<Ident> {
+        match &self.peek().kind {
+            TokenKind::Ident(name) if !is_reserved(name) => {
+                let name = name.clone();
+                let span = self.next().span;
+                Ok(Ident { name, span })
+            }
+            _ => self.unexpected("an identifier"),
+        }
+    }
+
+    /// `}` and an optional `;` closing a block
+    fn close_block(&mut self) -> Result<Span> {
+        let mut span = self.expect_punct("}")?;
+        if self.eat_punct(";") {
+            span = self.last_span();
+        }
+        Ok(span)
+    }
+
+    fn file(mut self) -> Result<File> {
+        let mut file = File {
+            includes: vec![],
+            modules: vec![],
+        };
+        loop {
+            if self.is_punct("#") {
+                file.includes.push(self.include()?);
+            } else if self.is_keyword("module") {
+                file.modules.push(self.module()?);
+            } else if self.peek().kind == TokenKind::Eof {
+                return Ok(file);
+            } else {
+                return self.unexpected("`#include` or `module`");
+            }
+        }
+    }
+
+    fn include(&mut self) -> Result<Include> {
+        let start = self.expect_punct("#")?;
+        self.expect_keyword("include")?;
+        match &self.peek().kind {
+            TokenKind::Str(path) => {
+                let path = path.clone();
+                let span = self.next().span;
+                Ok(Include {
+                    path,
+                    span: start.to(span),
+                })
+            }
+            _ => self.unexpected("a file path string"),
+        }
+    }
+
+    fn module(&mut self) -> Result<Module> {
+        let start = self.expect_keyword("module")?;
+        let name = self.ident()?;
+        self.expect_punct("{")?;
+        let mut items = vec![];
+        while !self.is_punct("}") {
+            items.push(if self.is_keyword("struct") {
+                Item::Struct(self.struct_()?)
+            } else if self.is_keyword("enum") {
+                Item::Enum(self.enum_()?)
+            } else if self.is_keyword("const") {
+                Item::Const(self.const_()?)
+            } else if self.is_keyword("key") {
+                Item::Key(self.key()?)
+            } else if self.is_keyword("interface") {
+                Item::Interface(self.interface()?)
+            } else {
+                return self.unexpected("`struct`, `enum`, `const`, `key`, `interface` or `}`");
+            });
+        }
+        let end = self.close_block()?;
+        let module = Module {
+            name,
+            items,
+            span: start.to(end),
+        };
+        self.check_module(&module)?;
+        Ok(module)
+    }
+
+    /// Item names are unique in a module
+    fn check_module(&self, module: &Module) -> Result<()> {
+        let mut names = HashMap::new();
+        for item in &module.items {
+            let name = match item {
+                Item::Struct(s) => &s.name,
+                Item::Enum(e) => &e.name,
+                Item::Const(c) => &c.name,
+                Item::Interface(i) => &i.name,
+                Item::Key(_) => continue,
+            };
+            if names.insert(&name.name, name.span).is_some() {
+                return Err(self.error(
+                    name.span,
+                    format!("`{}` is defined more than once", name.name),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn struct_(&mut self) -> Result<Struct> {
+        let start = self.expect_keyword("struct")?;
+        let name = self.ident()?;
+        self.expect_punct("{")?;
+        let mut fields: Vec<Field> = vec![];
+        while !self.is_punct("}") {
+            let field = self.field()?;
+            if let Some(other) = fields.iter().find(|f| f.tag == field.tag) {
+                return Err(self.error(
+                    field.span,
+                    format!("tag {} is already used by `{}`", field.tag, other.name.name),
+                ));
+            }
+            if fields.iter().any(|f| f.name.name == field.name.name) {
+                return Err(self.error(
+                    field.name.span,
+                    format!("field `{}` is defined more than once", field.name.name),
+                ));
+            }
+            fields.push(field);
+        }
+        let end = self.close_block()?;
+        Ok(Struct {
+            name,
+            fields,
+            span: start.to(end),
+        })
+    }
+
+    fn field(&mut self) -> Result<Field> {
+        let (tag, start) = match self.peek().kind {
+            TokenKind::Int(tag) => {
+                let span = self.peek().span;
+                let tag = u8::try_from(tag)
+                    .map_err(|_| self.error(span, format!("tag {} is out of 0..=255", tag)))?;
+                self.next();
+                (tag, span)
+            }
+            _ => return self.unexpected("a field tag or `}`"),
+        };
+        let require = if self.eat_keyword("require") {
+            true
+        } else if self.eat_keyword("optional") {
+            false
+        } else {
+            return self.unexpected("`require` or `optional`");
+        };
+        let ty = self.ty()?;
+        if ty.kind == TypeKind::Void {
+            return Err(self.error(ty.span, "a field can not be `void`"));
+        }
+        let name = self.ident()?;
+        let default = if self.eat_punct("=") {
+            Some(self.value()?)
+        } else {
+            None
+        };
+        let end = self.expect_punct(";")?;
+        Ok(Field {
+            tag,
+            require,
+            ty,
+            name,
+            default,
+            span: start.to(end),
+        })
+    }
+
+    fn ty(&mut self) -> Result<Type> {
+        let start = self.peek().span;
+        let keyword = match &self.peek().kind {
+            TokenKind::Ident(s) => s.clone(),
+            _ => return self.unexpected("a type"),
+        };
+        let kind = match keyword.as_str() {
+            "void" => TypeKind::Void,
+            "bool" => TypeKind::Bool,
+            "byte" => TypeKind::Byte,
+            "short" => TypeKind::Short,
+            "int" => TypeKind::Int,
+            "long" => TypeKind::Long,
+            "float" => TypeKind::Float,
+            "double" => TypeKind::Double,
+            "string" => TypeKind::String,
+            "unsigned" => {
+                self.next();
+                let kind = match &self.peek().kind {
+                    TokenKind::Ident(s) if s == "byte" => TypeKind::UnsignedByte,
+                    TokenKind::Ident(s) if s == "short" => TypeKind::UnsignedShort,
+                    TokenKind::Ident(s) if s == "int" => TypeKind::UnsignedInt,
+                    _ => return self.unexpected("`byte`, `short` or `int`"),
+                };
+                self.next();
+                return Ok(Type {
+                    kind,
+                    span: start.to(self.last_span()),
+                });
+            }
+            "vector" => {
+                self.next();
+                self.expect_punct("<")?;
+                let inner = self.ty()?;
+                let end = self.expect_punct(">")?;
+                return Ok(Type {
+                    kind: TypeKind::Vector(Box::new(inner)),
+                    span: start.to(end),
+                });
+            }
+            "map" => {
+                self.next();
+                self.expect_punct("<")?;
+                let key = self.ty()?;
+                self.expect_punct(",")?;
+                let value = self.ty()?;
+                let end = self.expect_punct(">")?;
+                return Ok(Type {
+                    kind: TypeKind::Map(Box::new(key), Box::new(value)),
+                    span: start.to(end),
+                });
+            }
+            _ => {
+                let path = self.path()?;
+                return Ok(Type {
+                    span: path.span,
+                    kind: TypeKind::Named(path),
+                });
+            }
+        };
+        self.next();
+        Ok(Type { kind, span: start })
+    }
+
+    fn path(&mut self) -> Result<Path> {
+        let mut segments = vec![self.ident()?];
+        while self.eat_punct("::") {
+            segments.push(self.ident()?);
+        }
+        Ok(Path {
+            span: segments[0].span.to(segments.last().unwrap().span),
+            segments,
+        })
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        let span = self.peek().span;
+        let kind = match &self.peek().kind {
+            TokenKind::Int(i) => ValueKind::Int(*i),
+            TokenKind::Float(f) => ValueKind::Float(*f),
+            TokenKind::Str(s) => ValueKind::String(s.clone()),
+            TokenKind::Ident(s) if s == "true" => ValueKind::Bool(true),
+            TokenKind::Ident(s) if s == "false" => ValueKind::Bool(false),
+            TokenKind::Ident(_) => {
+                let path = self.path()?;
+                return Ok(Value {
+                    span: path.span,
+                    kind: ValueKind::Path(path),
+                });
+            }
+            _ => return self.unexpected("a value"),
+        };
+        self.next();
+        Ok(Value { kind, span })
+    }
+
+    fn enum_(&mut self) -> Result<Enum> {
+        let start = self.expect_keyword("enum")?;
+        let name = self.ident()?;
+        self.expect_punct("{")?;
+        let mut variants: Vec<Variant> = vec![];
+        while !self.is_punct("}") {
+            let ident = self.ident()?;
+            if variants.iter().any(|v| v.name.name == ident.name) {
+                return Err(self.error(
+                    ident.span,
+                    format!("variant `{}` is defined more than once", ident.name),
+                ));
+            }
+            let value = if self.eat_punct("=") {
+                Some(self.value()?)
+            } else {
+                None
+            };
+            variants.push(Variant {
+                span: ident.span.to(self.last_span()),
+                name: ident,
+                value,
+            });
+            if !self.eat_punct(",") && !self.is_punct("}") {
+                return self.unexpected("`,` or `}`");
+            }
+        }
+        let end = self.close_block()?;
+        Ok(Enum {
+            name,
+            variants,
+            span: start.to(end),
+        })
+    }
+
+    fn const_(&mut self) -> Result<Const> {
+        let start = self.expect_keyword("const")?;
+        let ty = self.ty()?;
+        let name = self.ident()?;
+        self.expect_punct("=")?;
+        let value = self.value()?;
+        let end = self.expect_punct(";")?;
+        Ok(Const {
+            ty,
+            name,
+            value,
+            span: start.to(end),
+        })
+    }
+
+    fn key(&mut self) -> Result<Key> {
+        let start = self.expect_keyword("key")?;
+        self.expect_punct("[")?;
+        let name = self.ident()?;
+        let mut fields = vec![];
+        while self.eat_punct(",") {
+            fields.push(self.ident()?);
+        }
+        self.expect_punct("]")?;
+        let end = self.expect_punct(";")?;
+        Ok(Key {
+            name,
+            fields,
+            span: start.to(end),
+        })
+    }
+
+    fn interface(&mut self) -> Result<Interface> {
+        let start = self.expect_keyword("interface")?;
+        let name = self.ident()?;
+        self.expect_punct("{")?;
+        let mut operations = vec![];
+        while !self.is_punct("}") {
+            operations.push(self.operation()?);
+        }
+        let end = self.close_block()?;
+        Ok(Interface {
+            name,
+            operations,
+            span: start.to(end),
+        })
+    }
+
+    fn operation(&mut self) -> Result<Operation> {
+        let ret = self.ty()?;
+        let name = self.ident()?;
+        self.expect_punct("(")?;
+        let mut params = vec![];
+        while !self.is_punct(")") {
+            if !params.is_empty() {
+                self.expect_punct(",")?;
+            }
+            let start = self.peek().span;
+            let out = self.eat_keyword("out");
+            let routekey = self.eat_keyword("routekey");
+            let ty = self.ty()?;
+            if ty.kind == TypeKind::Void {
+                return Err(self.error(ty.span, "a parameter can not be `void`"));
+            }
+            let name = self.ident()?;
+            params.push(Param {
+                out,
+                routekey,
+                ty,
+                span: start.to(name.span),
+                name,
+            });
+        }
+        self.expect_punct(")")?;
+        let end = self.expect_punct(";")?;
+        Ok(Operation {
+            span: ret.span.to(end),
+            ret,
+            name,
+            params,
+        })
+    }
+}
+
+/// Keywords which can not name anything
+fn is_reserved(name: &str) -> bool {
+    matches!(
+        name,
+        "module"
+            | "struct"
+            | "enum"
+            | "const"
+            | "key"
+            | "interface"
+            | "require"
+            | "optional"
+            | "out"
+            | "routekey"
+            | "void"
+            | "bool"
+            | "byte"
+            | "short"
+            | "int"
+            | "long"
+            | "float"
+            | "double"
+            | "string"
+            | "unsigned"
+            | "vector"
+            | "map"
+            | "true"
+            | "false"
+    )
+}
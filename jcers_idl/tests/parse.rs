@@ -0,0 +1,210 @@
+use jcers_idl::ast::*;
+use jcers_idl::{parse, Span};
+
+fn names<'a>(idents: impl IntoIterator<Item = &'a Ident>) -> Vec<&'a str> {
+    idents.into_iter().map(|i| i.name.as_str()).collect()
+}
+
+#[test]
+fn test_parse_sample() {
+    let src = include_str!("data/sample.jce");
+    let file = parse(src).unwrap();
+    assert_eq!(file.includes[0].path, "base.jce");
+    let module = &file.modules[0];
+    assert_eq!(module.name.name, "Sample");
+    assert_eq!(module.items.len(), 6);
+
+    let Item::Const(max_len) = &module.items[0] else {
+        panic!()
+    };
+    assert_eq!(max_len.ty.kind, TypeKind::Int);
+    assert_eq!(max_len.value.kind, ValueKind::Int(256));
+    let Item::Const(name) = &module.items[1] else {
+        panic!()
+    };
+    assert_eq!(name.value.kind, ValueKind::String("sample\n".to_owned()));
+
+    let Item::Enum(color) = &module.items[2] else {
+        panic!()
+    };
+    assert_eq!(
+        names(color.variants.iter().map(|v| &v.name)),
+        ["RED", "GREEN", "BLUE", "DEFAULT"]
+    );
+    assert_eq!(color.variants[1].value, None);
+    assert_eq!(
+        color.variants[2].value.as_ref().unwrap().kind,
+        ValueKind::Int(-2)
+    );
+    let Some(Value {
+        kind: ValueKind::Path(path),
+        ..
+    }) = &color.variants[3].value
+    else {
+        panic!()
+    };
+    assert_eq!(path.name().name, "RED");
+
+    let Item::Struct(request) = &module.items[3] else {
+        panic!()
+    };
+    assert_eq!(
+        &src[request.span.start..request.span.end][..14],
+        "struct Request"
+    );
+    let fields = &request.fields;
+    assert_eq!(
+        names(fields.iter().map(|f| &f.name)),
+        ["id", "name", "payload", "items", "count", "color", "ratio", "enabled"]
+    );
+    assert!(fields[0].require && !fields[1].require);
+    assert_eq!(fields[0].ty.kind, TypeKind::Long);
+    assert_eq!(
+        fields[1].default.as_ref().unwrap().kind,
+        ValueKind::String("anonymous".to_owned())
+    );
+    let TypeKind::Vector(byte) = &fields[2].ty.kind else {
+        panic!()
+    };
+    assert_eq!(byte.kind, TypeKind::Byte);
+    let TypeKind::Map(key, value) = &fields[3].ty.kind else {
+        panic!()
+    };
+    assert_eq!(key.kind, TypeKind::String);
+    let TypeKind::Vector(item) = &value.kind else {
+        panic!()
+    };
+    let TypeKind::Named(item) = &item.kind else {
+        panic!()
+    };
+    assert_eq!(item.module().unwrap().name, "Base");
+    assert_eq!(item.name().name, "Item");
+    assert_eq!(fields[4].ty.kind, TypeKind::UnsignedInt);
+    assert_eq!(fields[4].tag, 4);
+    assert_eq!(
+        fields[6].default.as_ref().unwrap().kind,
+        ValueKind::Float(1.5)
+    );
+    assert_eq!(
+        fields[7].default.as_ref().unwrap().kind,
+        ValueKind::Bool(true)
+    );
+
+    let Item::Key(key) = &module.items[4] else {
+        panic!()
+    };
+    assert_eq!(key.name.name, "Request");
+    assert_eq!(names(&key.fields), ["id", "name"]);
+
+    let Item::Interface(service) = &module.items[5] else {
+        panic!()
+    };
+    let call = &service.operations[0];
+    assert_eq!(call.ret.kind, TypeKind::Int);
+    assert_eq!(
+        names(call.params.iter().map(|p| &p.name)),
+        ["req", "reply", "uid"]
+    );
+    assert!(!call.params[0].out && call.params[1].out && call.params[2].routekey);
+    assert_eq!(service.operations[1].ret.kind, TypeKind::Void);
+    assert!(service.operations[1].params.is_empty());
+}
+
+#[test]
+fn test_parse_errors() {
+    let cases = [
+        (
+            "module M { struct S { 0 require int a } };",
+            1,
+            39,
+            "expected `;`, found `}`",
+        ),
+        (
+            "module M {\n  struct S {\n    0 require int a;\n    0 optional int b;\n  };\n};",
+            4,
+            5,
+            "tag 0 is already used by `a`",
+        ),
+        (
+            "module M { struct S { 256 require int a; }; };",
+            1,
+            23,
+            "tag 256 is out of 0..=255",
+        ),
+        (
+            "module M { struct S { 0 needed int a; }; };",
+            1,
+            25,
+            "expected `require` or `optional`, found `needed`",
+        ),
+        (
+            "module M { enum E { A, A }; };",
+            1,
+            24,
+            "variant `A` is defined more than once",
+        ),
+        (
+            "module M { struct S {}; enum S { A }; };",
+            1,
+            30,
+            "`S` is defined more than once",
+        ),
+        (
+            "module M { const int A = ; };",
+            1,
+            26,
+            "expected a value, found `;`",
+        ),
+        (
+            "module M { struct S { 0 require unsigned long a; }; };",
+            1,
+            42,
+            "expected `byte`, `short` or `int`, found `long`",
+        ),
+        ("module M { /* open", 1, 12, "unterminated block comment"),
+        ("#include \"a.jce", 1, 10, "unterminated string"),
+        (
+            "#include",
+            1,
+            9,
+            "expected a file path string, found end of file",
+        ),
+        (
+            "#include module",
+            1,
+            10,
+            "expected a file path string, found `module`",
+        ),
+        ("module M { $ };", 1, 12, "unexpected character `$`"),
+        (
+            "module M { struct S { 0 require int a; };",
+            1,
+            42,
+            "expected `struct`, `enum`, `const`, `key`, `interface` or `}`, found end of file",
+        ),
+    ];
+    for (src, line, column, message) in cases {
+        let e = parse(src).unwrap_err();
+        assert_eq!(
+            (e.line, e.column, e.message.as_str()),
+            (line, column, message),
+            "{}",
+            src
+        );
+    }
+}
+
+#[test]
+fn test_render_error() {
+    let src = "module M {\r\n    struct S {\r\n        0 require int a\r\n    };\r\n};\r\n";
+    let e = parse(src).unwrap_err();
+    assert_eq!(
+        e.span,
+        Span::new(src.find("}").unwrap(), src.find("}").unwrap() + 1)
+    );
+    assert_eq!(e.to_string(), "4:5: expected `;`, found `}`");
+    assert_eq!(
+        e.render(src, "test.jce"),
+        "error: expected `;`, found `}`\n --> test.jce:4:5\n  |\n4 |     };\n  |     ^\n"
+    );
+}
@@ -0,0 +1,308 @@
+use proc_macro2::Span;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Attribute, Field, Ident, Token, WherePredicate};
+
+/// What a field missing from the input decodes to
+pub(crate) enum Presence {
+    /// an error
+    Require,
+    /// `Default::default()`
+    Optional,
+    /// the expression
+    Default(Box<syn::Expr>),
+}
+
+/// `#[jce(...)]` of a field
+pub(crate) struct FieldAttrs {
+    /// `None` for a `skip` or `unknown` field
+    pub(crate) tag: Option<u8>,
+    /// span of the tag, or of the attribute for a field without one
+    pub(crate) span: Span,
+    /// the field keeps the fields with other tags, which are written back
+    pub(crate) unknown: bool,
+    /// `None` if not given, which is `Require`, or `None` for an `Option`
+    pub(crate) presence: Option<Presence>,
+    /// module with the `jce_get` and `jce_put` functions of the field
+    pub(crate) with: Option<syn::Path>,
+    /// function the field is not written if it returns `true` for
+    pub(crate) skip_serializing_if: Option<syn::Path>,
+}
+
+/// One item of `#[jce(...)]`
+enum Arg {
+    Tag(syn::LitInt),
+    Presence(Ident, Presence),
+    With(Ident, syn::Path),
+    Skip(Ident),
+    Unknown(Ident),
+    SkipSerializingIf(Ident, syn::Path),
+}
+
+/// `= "path"`
+fn parse_path(input: ParseStream) -> syn::Result<syn::Path> {
+    input.parse::<Token![=]>()?;
+    input.parse::<syn::LitStr>()?.parse()
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let lookahead = input.lookahead1();
+        if lookahead.peek(syn::LitInt) {
+            return Ok(Arg::Tag(input.parse()?));
+        }
+        if !lookahead.peek(Ident) {
+            return Err(lookahead.error());
+        }
+        let ident: Ident = input.parse()?;
+        let presence = match ident.to_string().as_str() {
+            "require" => Presence::Require,
+            "optional" => Presence::Optional,
+            "default" => {
+                input.parse::<Token![=]>()?;
+                Presence::Default(Box::new(input.parse()?))
+            }
+            "with" => return Ok(Arg::With(ident, parse_path(input)?)),
+            "skip" => return Ok(Arg::Skip(ident)),
+            "unknown" => return Ok(Arg::Unknown(ident)),
+            "skip_serializing_if" => {
+                let path = parse_path(input)?;
+                return Ok(Arg::SkipSerializingIf(ident, path));
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    format!("unknown jce attribute `{}`", ident),
+                ))
+            }
+        };
+        Ok(Arg::Presence(ident, presence))
+    }
+}
+
+fn parse_tag(lit: &syn::LitInt) -> syn::Result<u8> {
+    let tag: i64 = lit.base10_parse()?;
+    u8::try_from(tag).map_err(|_| {
+        syn::Error::new(
+            lit.span(),
+            format!("jce tag {} is out of range, a tag is 0 to 255", tag),
+        )
+    })
+}
+
+pub(crate) fn parse_field_attrs(attrs: &[Attribute], field: &Field) -> syn::Result<FieldAttrs> {
+    let mut jce_attrs = attrs.iter().filter(|attr| attr.path.is_ident("jce"));
+    let attr = match jce_attrs.next() {
+        Some(attr) => attr,
+        None => {
+            return Err(syn::Error::new(
+                field.span(),
+                "JceGet expected a `jce` attribute",
+            ))
+        }
+    };
+    if let Some(attr) = jce_attrs.next() {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "a field takes a single `jce` attribute",
+        ));
+    }
+    let args = attr.parse_args_with(Punctuated::<Arg, Token![,]>::parse_terminated)?;
+    if args.is_empty() {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "empty jce attribute, expected a tag as `#[jce(0)]`",
+        ));
+    }
+    let len = args.len();
+    let mut tag = None;
+    let mut unknown = false;
+    let mut presence: Option<Presence> = None;
+    let mut with = None;
+    let mut skip = None;
+    let mut skip_serializing_if = None;
+    // span of an attribute `skip` can not be given with
+    let mut not_skip = None;
+    for arg in args {
+        match arg {
+            Arg::Tag(lit) => match tag {
+                Some(_) => return Err(syn::Error::new(lit.span(), "the tag is already given")),
+                None => {
+                    not_skip = Some(lit.span());
+                    tag = Some((parse_tag(&lit)?, lit.span()));
+                }
+            },
+            Arg::Presence(ident, new) => {
+                // `skip` takes a `default`
+                if !matches!(new, Presence::Default(_)) {
+                    not_skip = Some(ident.span());
+                }
+                presence = Some(match (presence, new) {
+                    (None, new) => new,
+                    // `default` is optional already
+                    (Some(Presence::Optional), new @ Presence::Default(_))
+                    | (Some(new @ Presence::Default(_)), Presence::Optional) => new,
+                    _ => {
+                        return Err(syn::Error::new(
+                            ident.span(),
+                            "only one of `require`, `optional` and `default` may be given",
+                        ))
+                    }
+                })
+            }
+            Arg::With(ident, path) => {
+                not_skip = Some(ident.span());
+                with = Some(path);
+            }
+            Arg::Skip(ident) => skip = Some(ident),
+            Arg::Unknown(ident) if len > 1 => {
+                return Err(syn::Error::new(
+                    ident.span(),
+                    "an `unknown` field takes no other attribute",
+                ))
+            }
+            Arg::Unknown(_) => unknown = true,
+            Arg::SkipSerializingIf(ident, path) => {
+                not_skip = Some(ident.span());
+                skip_serializing_if = Some(path);
+            }
+        }
+    }
+    match (skip, not_skip) {
+        (Some(_), Some(span)) => Err(syn::Error::new(
+            span,
+            "a `skip` field only takes a `default`",
+        )),
+        (None, _) if tag.is_none() && !unknown => Err(syn::Error::new(
+            attr.path.span(),
+            "jce attribute expected a number",
+        )),
+        _ => Ok(FieldAttrs {
+            tag: tag.map(|(tag, _)| tag),
+            span: tag.map_or_else(|| attr.span(), |(_, span)| span),
+            unknown,
+            presence,
+            with,
+            skip_serializing_if,
+        }),
+    }
+}
+
+/// Where predicates given as a string, as `#[jce(bound = "T: Clone")]`
+pub(crate) type Predicates = Punctuated<WherePredicate, Token![,]>;
+
+/// Bounds replacing the `T: JceGet` and `T: JcePut` added for each type
+/// parameter, `None` for the ones not given
+#[derive(Default)]
+pub(crate) struct Bounds {
+    pub(crate) get: Option<Predicates>,
+    pub(crate) put: Option<Predicates>,
+}
+
+/// `#[jce(...)]` of a struct or enum
+#[derive(Default)]
+pub(crate) struct ContainerAttrs {
+    /// encode as the only field
+    pub(crate) transparent: bool,
+    pub(crate) bounds: Bounds,
+    /// tars type name of `JceTypeName`
+    pub(crate) name: Option<syn::LitStr>,
+}
+
+/// One item of `#[jce(...)]` of a struct or enum
+enum ContainerArg {
+    Transparent,
+    /// `bound = "..."` for both derives, or `bound(get = "...", put = "...")`
+    Bound(Bounds),
+    Name(syn::LitStr),
+}
+
+fn parse_predicates(input: ParseStream) -> syn::Result<Predicates> {
+    input.parse::<Token![=]>()?;
+    let lit: syn::LitStr = input.parse()?;
+    lit.parse_with(Predicates::parse_terminated)
+}
+
+impl Parse for ContainerArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident: Ident = input.parse()?;
+        match ident.to_string().as_str() {
+            "transparent" => Ok(ContainerArg::Transparent),
+            "name" => {
+                input.parse::<Token![=]>()?;
+                Ok(ContainerArg::Name(input.parse()?))
+            }
+            "bound" if input.peek(Token![=]) => {
+                let predicates = parse_predicates(input)?;
+                Ok(ContainerArg::Bound(Bounds {
+                    get: Some(predicates.clone()),
+                    put: Some(predicates),
+                }))
+            }
+            "bound" => {
+                let content;
+                syn::parenthesized!(content in input);
+                let mut bounds = Bounds::default();
+                for (ident, predicates) in content.parse_terminated::<_, Token![,]>(|input| {
+                    let ident: Ident = input.parse()?;
+                    Ok((ident, parse_predicates(input)?))
+                })? {
+                    match ident.to_string().as_str() {
+                        "get" => bounds.get = Some(predicates),
+                        "put" => bounds.put = Some(predicates),
+                        _ => return Err(syn::Error::new(ident.span(), "expected `get` or `put`")),
+                    }
+                }
+                Ok(ContainerArg::Bound(bounds))
+            }
+            _ => Err(syn::Error::new(
+                ident.span(),
+                format!("unknown jce attribute `{}`", ident),
+            )),
+        }
+    }
+}
+
+pub(crate) fn parse_container_attrs(attrs: &[Attribute]) -> syn::Result<ContainerAttrs> {
+    let mut container = ContainerAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("jce")) {
+        let args = attr.parse_args_with(Punctuated::<ContainerArg, Token![,]>::parse_terminated)?;
+        for arg in args {
+            match arg {
+                ContainerArg::Transparent => container.transparent = true,
+                ContainerArg::Name(name) => container.name = Some(name),
+                ContainerArg::Bound(bounds) => {
+                    if bounds.get.is_some() {
+                        container.bounds.get = bounds.get;
+                    }
+                    if bounds.put.is_some() {
+                        container.bounds.put = bounds.put;
+                    }
+                }
+            }
+        }
+    }
+    Ok(container)
+}
+
+/// `T` of a field of type `Option<T>`
+pub(crate) fn option_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    let path = match ty {
+        syn::Type::Path(ty) if ty.qself.is_none() => &ty.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        syn::PathArguments::AngleBracketed(args) if args.args.len() == 1 => {
+            match args.args.first() {
+                Some(syn::GenericArgument::Type(ty)) => Some(ty),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
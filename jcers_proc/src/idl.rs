@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+use proc_macro2::{Delimiter, Span, TokenStream, TokenTree};
+use quote::quote;
+
+/// IDL source written back from the macro input, with the span of each token
+#[derive(Default)]
+struct Source {
+    src: String,
+    spans: Vec<(usize, usize, Span)>,
+}
+
+impl Source {
+    fn push(&mut self, text: &str, span: Span) {
+        let start = self.src.len();
+        self.src.push_str(text);
+        self.spans.push((start, self.src.len(), span));
+    }
+
+    fn write(&mut self, input: TokenStream) {
+        let mut tokens = input.into_iter().peekable();
+        while let Some(token) = tokens.next() {
+            let mut joint = false;
+            match token {
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::None => ("", ""),
+                    };
+                    self.push(open, group.span_open());
+                    self.src.push(' ');
+                    self.write(group.stream());
+                    self.push(close, group.span_close());
+                }
+                TokenTree::Punct(punct) => {
+                    self.push(&punct.as_char().to_string(), punct.span());
+                    // `::`, and `-1` which is a `-` before the literal
+                    joint = punct.spacing() == proc_macro2::Spacing::Joint
+                        || (punct.as_char() == '-'
+                            && matches!(tokens.peek(), Some(TokenTree::Literal(_))));
+                }
+                TokenTree::Ident(ident) => self.push(&ident.to_string(), ident.span()),
+                TokenTree::Literal(lit) => self.push(&lit.to_string(), lit.span()),
+            }
+            if !joint {
+                self.src.push(' ');
+            }
+        }
+    }
+
+    /// Span of the token at `offset` of the source
+    fn span_at(&self, offset: usize) -> Span {
+        self.spans
+            .iter()
+            .rev()
+            .find(|(start, ..)| *start <= offset)
+            .map_or_else(Span::call_site, |(.., span)| *span)
+    }
+}
+
+fn manifest_dir() -> PathBuf {
+    std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+/// The modules, and `include_bytes!` of the files read so they are tracked
+fn output(modules: TokenStream, paths: Vec<PathBuf>) -> TokenStream {
+    let paths = paths.iter().map(|p| p.display().to_string());
+    quote! {
+        #(const _: &[u8] = include_bytes!(#paths);)*
+        #modules
+    }
+}
+
+pub(crate) fn jce_idl(input: TokenStream) -> syn::Result<TokenStream> {
+    let mut source = Source::default();
+    source.write(input);
+    match jcers_build::Config::new().expand(source.src.clone(), None, &manifest_dir()) {
+        Ok((modules, paths)) => Ok(output(modules, paths)),
+        Err(e) => Err(match e.at {
+            Some((message, span)) => syn::Error::new(source.span_at(span.start), message),
+            None => syn::Error::new(Span::call_site(), e.rendered),
+        }),
+    }
+}
+
+pub(crate) fn jce_include(input: TokenStream) -> syn::Result<TokenStream> {
+    let lit: syn::LitStr = syn::parse2(input)?;
+    let path = manifest_dir().join(lit.value());
+    let (src, path) = std::fs::read_to_string(&path)
+        .and_then(|src| Ok((src, std::fs::canonicalize(&path)?)))
+        .map_err(|e| syn::Error::new(lit.span(), format!("{}: {}", path.display(), e)))?;
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    match jcers_build::Config::new().expand(src, Some(path), &dir) {
+        Ok((modules, paths)) => Ok(output(modules, paths)),
+        Err(e) => Err(syn::Error::new(lit.span(), e.rendered)),
+    }
+}
@@ -2,13 +2,31 @@ use proc_macro::TokenStream;
 use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
 use syn::spanned::Spanned;
-use syn::{self, Attribute, DataStruct, DeriveInput, Field, Fields, Ident};
+use syn::{self, DeriveInput, Field, Fields, Generics, Ident};
+
+use attr::{
+    option_inner, parse_container_attrs, parse_field_attrs, ContainerAttrs, FieldAttrs, Predicates,
+    Presence,
+};
+
+mod attr;
+#[cfg(feature = "idl")]
+mod idl;
 
 #[proc_macro_derive(JceGet, attributes(jce))]
 pub fn jce_get_derive(input: TokenStream) -> TokenStream {
-    match parse_input(syn::parse_macro_input!(input as DeriveInput))
-        .and_then(|(struct_name, struct_data)| gen_get_body(struct_name, struct_data.fields))
-    {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match parse_input(input).and_then(|(input, container)| match input {
+        Input::Struct(name, generics, fields) => {
+            let generics = get_generics(&generics, container.bounds.get.as_ref());
+            gen_get_body(name, generics, fields)
+        }
+        Input::Transparent(name, generics, field) => {
+            let generics = get_generics(&generics, container.bounds.get.as_ref());
+            gen_get_transparent(name, generics, *field)
+        }
+        Input::Enum(name, generics, variants) => gen_get_enum(name, generics, variants),
+    }) {
         Ok(token) => token.into(),
         Err(errors) => to_compile_errors(errors).into(),
     }
@@ -16,117 +34,666 @@ pub fn jce_get_derive(input: TokenStream) -> TokenStream {
 
 #[proc_macro_derive(JcePut, attributes(jce))]
 pub fn jce_put_derive(input: TokenStream) -> TokenStream {
-    match parse_input(syn::parse_macro_input!(input as syn::DeriveInput))
-        .and_then(|(struct_name, struct_data)| gen_put_body(struct_name, struct_data.fields))
-    {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match parse_input(input).and_then(|(input, container)| match input {
+        Input::Struct(name, generics, fields) => {
+            let generics = put_generics(&generics, container.bounds.put.as_ref());
+            gen_put_body(name, generics, fields)
+        }
+        Input::Transparent(name, generics, field) => {
+            let generics = put_generics(&generics, container.bounds.put.as_ref());
+            gen_put_transparent(name, generics, *field)
+        }
+        Input::Enum(name, generics, variants) => gen_put_enum(name, generics, variants),
+    }) {
         Ok(token) => token.into(),
         Err(errors) => to_compile_errors(errors).into(),
     }
 }
 
-fn parse_input(input: DeriveInput) -> Result<(Ident, DataStruct), Vec<syn::Error>> {
-    let struct_name = input.ident;
-    let struct_data = input.data;
-    if let syn::Data::Struct(data_struct) = struct_data {
-        if let syn::Fields::Named(_) = data_struct.fields {
-            Ok((struct_name, data_struct))
-        } else {
-            Err(vec![syn::Error::new(
-                struct_name.span(),
-                "only named fields are supported",
-            )])
+#[proc_macro_derive(JceTypeName, attributes(jce))]
+pub fn jce_type_name_derive(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    match parse_input(input).map(|(input, container)| gen_type_name(input, container.name)) {
+        Ok(token) => token.into(),
+        Err(errors) => to_compile_errors(errors).into(),
+    }
+}
+
+/// Expand inline jce / tars IDL into a `pub mod` per module, with the types
+/// `jcers_build` generates
+///
+/// `#include` is looked up from the crate root.
+#[cfg(feature = "idl")]
+#[proc_macro]
+pub fn jce_idl(input: TokenStream) -> TokenStream {
+    match idl::jce_idl(input.into()) {
+        Ok(token) => token.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Like `jce_idl!`, for the IDL file at a path from the crate root
+#[cfg(feature = "idl")]
+#[proc_macro]
+pub fn jce_include(input: TokenStream) -> TokenStream {
+    match idl::jce_include(input.into()) {
+        Ok(token) => token.into(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// What the derives are generated for
+enum Input {
+    /// struct with named or positional fields, each with a tag
+    Struct(Ident, Generics, Fields),
+    /// `#[jce(transparent)]` struct, encoded as its only field
+    Transparent(Ident, Generics, Box<Field>),
+    /// fieldless enum, encoded as its `i32` discriminant
+    Enum(Ident, Generics, Vec<Ident>),
+}
+
+/// The input, with its `#[jce(...)]`
+fn parse_input(input: DeriveInput) -> Result<(Input, ContainerAttrs), Vec<syn::Error>> {
+    let container = parse_container_attrs(&input.attrs).map_err(|e| vec![e])?;
+    let name = input.ident;
+    let input = match input.data {
+        syn::Data::Struct(data) if container.transparent => {
+            let mut fields = data.fields.into_iter();
+            match (fields.next(), fields.next()) {
+                (Some(field), None) => {
+                    Ok(Input::Transparent(name, input.generics, Box::new(field)))
+                }
+                _ => Err(vec![syn::Error::new(
+                    name.span(),
+                    "a transparent struct should have exactly one field",
+                )]),
+            }
         }
-    } else {
-        Err(vec![syn::Error::new(
-            struct_name.span(),
-            "JceGet expected a struct",
-        )])
-    }
-}
-
-fn parse_attrs(attrs: &Vec<Attribute>, field: &Field) -> Result<u8, Vec<syn::Error>> {
-    for meta in attrs.iter().map(|attr| attr.parse_meta()) {
-        if let Ok(syn::Meta::List(list)) = meta {
-            if list.path.is_ident("jce") {
-                if let syn::NestedMeta::Lit(syn::Lit::Int(lit_int)) = list.nested.first().unwrap() {
-                    return Ok(lit_int.base10_parse().unwrap());
-                } else {
-                    return Err(vec![syn::Error::new(
-                        field.span(),
-                        "jce attribute expected a number",
-                    )]);
+        syn::Data::Struct(data) => Ok(Input::Struct(name, input.generics, data.fields)),
+        syn::Data::Enum(_) if container.transparent => Err(vec![syn::Error::new(
+            name.span(),
+            "only a struct can be transparent",
+        )]),
+        syn::Data::Enum(data) => {
+            let mut errors = vec![];
+            let mut variants = vec![];
+            for variant in data.variants {
+                match variant.fields {
+                    Fields::Unit => variants.push(variant.ident),
+                    _ => errors.push(syn::Error::new(
+                        variant.ident.span(),
+                        "only fieldless enums are supported",
+                    )),
                 }
             }
+            match errors.is_empty() {
+                true => Ok(Input::Enum(name, input.generics, variants)),
+                false => Err(errors),
+            }
         }
+        syn::Data::Union(_) => Err(vec![syn::Error::new(
+            name.span(),
+            "JceGet expected a struct or an enum",
+        )]),
+    }?;
+    Ok((input, container))
+}
+
+/// `self.#member` of a field, by name or position
+fn field_member(i: usize, field: &Field) -> (syn::Member, String) {
+    match &field.ident {
+        Some(ident) => (syn::Member::Named(ident.clone()), ident.to_string()),
+        None => (syn::Member::Unnamed(syn::Index::from(i)), i.to_string()),
     }
-    Err(vec![syn::Error::new(
-        field.span(),
-        "JceGet expected a `jce` attribute",
-    )])
 }
 
-fn gen_get_body(struct_name: Ident, fields: Fields) -> Result<TokenStream2, Vec<syn::Error>> {
-    // let mut ts = TokenStream2::default();
-    let mut idents_vec = vec![];
-    let mut tys_vec = vec![];
-    let mut tags_vec = vec![];
-    for field in fields.iter() {
-        tags_vec.push(parse_attrs(&field.attrs, &field)?);
-        idents_vec.push(field.ident.as_ref().unwrap());
-        tys_vec.push(&field.ty);
-        // fields_vec.push((field.ident.as_ref().unwrap(), &field.ty, tag));
-        // let ident = field.ident.as_ref().unwrap();
-        // let ty = &field.ty;
+/// Add `T: #bound` for each type parameter, or the predicates given instead
+fn add_bounds(generics: &mut Generics, predicates: Option<&Predicates>, bound: TokenStream2) {
+    let predicates: Vec<syn::WherePredicate> = match predicates {
+        Some(predicates) => predicates.iter().cloned().collect(),
+        None => generics
+            .type_params()
+            .map(|param| {
+                let ident = &param.ident;
+                syn::parse_quote!(#ident: #bound)
+            })
+            .collect(),
+    };
+    generics.make_where_clause().predicates.extend(predicates);
+}
+
+/// The lifetime a type with lifetimes is read with, which outlives them all
+fn de_lifetime() -> syn::Lifetime {
+    syn::Lifetime::new("'__de", proc_macro2::Span::call_site())
+}
+
+/// Generics of the `JceGet` impl, or of `JceGetRef<'__de>` for a type with
+/// lifetimes, which borrows from the input
+fn get_generics(generics: &Generics, predicates: Option<&Predicates>) -> Generics {
+    let mut generics = generics.clone();
+    let lifetimes: Vec<_> = generics.lifetimes().map(|l| l.lifetime.clone()).collect();
+    if lifetimes.is_empty() {
+        add_bounds(&mut generics, predicates, quote!(jcers::JceGet));
+    } else {
+        let de = de_lifetime();
+        add_bounds(&mut generics, predicates, quote!(jcers::JceGetRef<#de>));
+        generics
+            .params
+            .insert(0, syn::parse_quote!(#de: #(#lifetimes)+*));
     }
-    Ok(quote! {
-        impl jcers::JceGet for #struct_name {
-            fn jce_get<B: bytes::Buf + ?Sized>(jce: &mut jcers::Jce<B>) -> jcers::JceResult<Self> {
-                let sub = jce.head.ty == jcers::JceType::Struct;
-                Ok(if sub {
-                    let mut sub_jce = jce.sub_jce();
-                    let r = #struct_name {
-                        #(
-                            #idents_vec: sub_jce.get_by_tag::<#tys_vec>(#tags_vec)?
-                        ),*
+    generics
+}
+
+fn put_generics(generics: &Generics, predicates: Option<&Predicates>) -> Generics {
+    let mut generics = generics.clone();
+    add_bounds(&mut generics, predicates, quote!(jcers::JcePut));
+    generics
+}
+
+/// Whether `get_generics` gave a `JceGetRef` impl
+fn is_borrowed(generics: &Generics) -> bool {
+    generics.lifetimes().any(|l| l.lifetime == de_lifetime())
+}
+
+/// `impl ... for #name ...` of the get derives, with generics from
+/// `get_generics`
+fn get_impl(name: &Ident, generics: &Generics, items: TokenStream2) -> TokenStream2 {
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    // the type is written without `'__de`
+    let mut ty_generics = generics.clone();
+    ty_generics.params = ty_generics
+        .params
+        .into_iter()
+        .filter(
+            |param| !matches!(param, syn::GenericParam::Lifetime(l) if l.lifetime == de_lifetime()),
+        )
+        .collect();
+    let (_, ty_generics, _) = ty_generics.split_for_impl();
+    let tr = match is_borrowed(generics) {
+        true => {
+            let de = de_lifetime();
+            quote!(jcers::JceGetRef<#de>)
+        }
+        false => quote!(jcers::JceGet),
+    };
+    quote! {
+        impl #impl_generics #tr for #name #ty_generics #where_clause {
+            #items
+        }
+    }
+}
+
+/// The attributes of each field, with the errors of all of them and the
+/// duplicate tags
+fn parse_fields(fields: &Fields) -> Result<Vec<FieldAttrs>, Vec<syn::Error>> {
+    let mut errors = vec![];
+    let mut attrs = vec![];
+    // tags and the fields they are on
+    let mut used: Vec<(u8, String)> = vec![];
+    let mut unknown = false;
+    for (i, field) in fields.iter().enumerate() {
+        let field_attrs = match parse_field_attrs(&field.attrs, field) {
+            Ok(field_attrs) => field_attrs,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        if field_attrs.unknown && std::mem::replace(&mut unknown, true) {
+            errors.push(syn::Error::new(
+                field_attrs.span,
+                "only one field can be `unknown`",
+            ));
+        }
+        if let Some(tag) = field_attrs.tag {
+            match used.iter().find(|(used, _)| *used == tag) {
+                Some((_, name)) => errors.push(syn::Error::new(
+                    field_attrs.span,
+                    format!("jce tag {} is already used by field `{}`", tag, name),
+                )),
+                None => used.push((tag, field_member(i, field).1)),
+            }
+        }
+        attrs.push(field_attrs);
+    }
+    match errors.is_empty() {
+        true => Ok(attrs),
+        false => Err(errors),
+    }
+}
+
+/// A warning at the first tag lower than one before it, as the fields are
+/// written in order and a forward-only reader can not go back for it
+///
+/// A proc macro can not warn on stable, so this is a use of a deprecated
+/// const, with the span of the tag. Both derives emit it, so a struct
+/// deriving only one is warned too; the const is named after the struct, and
+/// rustc reports the two identical warnings once.
+fn tag_order_warning(struct_name: &Ident, attrs: &[FieldAttrs]) -> Option<TokenStream2> {
+    let mut last = None;
+    for attr in attrs {
+        let tag = match attr.tag {
+            Some(tag) => tag,
+            None => continue,
+        };
+        match last {
+            Some(last) if tag < last => {
+                let note = format!(
+                    "jce tag {} is declared after tag {}, fields are written in declaration order and readers may expect ascending tags",
+                    tag, last
+                );
+                let name = quote::format_ident!("jce_tag_order_of_{}", struct_name);
+                let warning = quote::format_ident!("{}", name, span = attr.span);
+                return Some(quote! {
+                    const _: () = {
+                        #[deprecated(note = #note)]
+                        #[allow(non_upper_case_globals, non_snake_case)]
+                        const #name: () = ();
+                        #warning
                     };
-                    jce.end_struct()?;
-                    r
-                } else {
-                    #struct_name {
-                        #(
-                            #idents_vec: jce.get_by_tag::<#tys_vec>(#tags_vec)?
-                        ),*
+                });
+            }
+            _ => last = Some(tag),
+        }
+    }
+    None
+}
+
+fn gen_get_body(
+    struct_name: Ident,
+    generics: Generics,
+    fields: Fields,
+) -> Result<TokenStream2, Vec<syn::Error>> {
+    let borrowed = is_borrowed(&generics);
+    let struct_str = struct_name.to_string();
+    let get_by_tag = match borrowed {
+        true => quote!(get_ref_by_tag),
+        false => quote!(get_by_tag),
+    };
+    let mut members_vec = vec![];
+    let mut values_vec = vec![];
+    let mut tags_vec = vec![];
+    let mut vars_vec = vec![];
+    let mut reads_vec = vec![];
+    // the other tags are kept in the `unknown` field, or skipped
+    let mut unknown_arm = quote!(_ => {});
+    let mut unknown_var = TokenStream2::default();
+    let attrs = parse_fields(&fields)?;
+    let warning = tag_order_warning(&struct_name, &attrs);
+    for (i, (field, attrs)) in fields.iter().zip(attrs).enumerate() {
+        let (member, name) = field_member(i, field);
+        members_vec.push(member);
+        if attrs.unknown {
+            let ty = &field.ty;
+            unknown_var = quote! {
+                let mut __unknown = <#ty as ::std::default::Default>::default();
+            };
+            unknown_arm = quote! {
+                tag => {
+                    __unknown.insert(
+                        tag,
+                        jce.#get_by_tag(tag).map_err(|e| e.in_field(#struct_str, #name))?,
+                    );
+                }
+            };
+            values_vec.push(quote!(__unknown));
+            continue;
+        }
+        let tag = match attrs.tag {
+            Some(tag) => tag,
+            // `skip`
+            None => {
+                values_vec.push(match attrs.presence {
+                    Some(Presence::Default(expr)) => quote!(#expr),
+                    _ => quote!(::std::default::Default::default()),
+                });
+                continue;
+            }
+        };
+        // the module reads the whole field
+        let option = match attrs.with {
+            Some(_) => None,
+            None => option_inner(&field.ty),
+        };
+        let ty = option.unwrap_or(&field.ty);
+        let var = quote::format_ident!("__field{}", i);
+        // the value of a field missing from the input
+        let missing = match (attrs.presence, option) {
+            (Some(Presence::Require), Some(_)) => {
+                return Err(vec![syn::Error::new(
+                    field.ty.span(),
+                    "a required field can not be an `Option`",
+                )])
+            }
+            (Some(Presence::Require) | None, None) => quote! {
+                return Err(jce.field_not_found(#tag).in_field(#struct_str, #name))
+            },
+            (None, Some(_)) => quote!(None),
+            (Some(Presence::Optional), _) => quote!(::std::default::Default::default()),
+            (Some(Presence::Default(expr)), _) => quote!(#expr),
+        };
+        let found = match option {
+            Some(_) => quote!(Some(v)),
+            None => quote!(v),
+        };
+        values_vec.push(quote! {
+            match #var {
+                Some(v) => #found,
+                None => #missing,
+            }
+        });
+        let read = match attrs.with {
+            // the module reads the current head, an Empty one included
+            Some(with) => quote!(jce.get_with(#with::jce_get)),
+            None => quote!(jce.#get_by_tag::<#ty>(#tag)),
+        };
+        reads_vec.push(quote! {
+            #read.map_err(|e| e.in_field(#struct_str, #name))?
+        });
+        vars_vec.push(var);
+        tags_vec.push(tag);
+    }
+    // fields are read in wire order, so tags may come in any order
+    let body = quote! {
+        #(
+            let mut #vars_vec = None;
+        )*
+        #unknown_var
+        jce.visit_fields(|jce| {
+            match jce.head.tag {
+                #(
+                    #tags_vec => {
+                        #vars_vec = Some(#reads_vec);
                     }
-                })
+                )*
+                #unknown_arm
+            }
+            Ok(())
+        })?;
+        Ok(#struct_name {
+            #(
+                #members_vec: #values_vec,
+            )*
+        })
+    };
+    // a struct with a lifetime borrows from the input, see `jcers::from_slice`
+    if borrowed {
+        let de = de_lifetime();
+        let mut ts = get_impl(
+            &struct_name,
+            &generics,
+            quote! {
+                fn jce_get_ref(jce: &mut jcers::Jce<&#de [u8]>) -> jcers::JceResult<Self> {
+                    #body
+                }
+            },
+        );
+        ts.extend(warning);
+        return Ok(ts);
+    }
+    let mut generics = generics;
+    if generics.type_params().next().is_some() {
+        let (_, ty_generics, _) = generics.split_for_impl();
+        let predicate: syn::WherePredicate =
+            syn::parse_quote!(#struct_name #ty_generics: ::std::default::Default);
+        generics.make_where_clause().predicates.push(predicate);
+    }
+    let mut ts = get_impl(
+        &struct_name,
+        &generics,
+        quote! {
+            fn jce_get<B: bytes::Buf + ?Sized>(jce: &mut jcers::Jce<B>) -> jcers::JceResult<Self> {
+                #body
             }
 
             fn empty() -> jcers::JceResult<Self> {
-                Ok(#struct_name::default())
+                Ok(<Self as ::std::default::Default>::default())
             }
-        }
-    })
+        },
+    );
+    ts.extend(warning);
+    Ok(ts)
 }
 
-fn gen_put_body(struct_name: Ident, fields: Fields) -> Result<TokenStream2, Vec<syn::Error>> {
-    let mut ts = TokenStream2::default();
-    for field in fields.iter() {
-        let tag = parse_attrs(&field.attrs, &field)?;
-        let ident = field.ident.as_ref().unwrap();
+fn gen_put_body(
+    struct_name: Ident,
+    generics: Generics,
+    fields: Fields,
+) -> Result<TokenStream2, Vec<syn::Error>> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let attrs = parse_fields(&fields)?;
+    // the `unknown` field is written between the others, in tag order
+    let unknown = fields
+        .iter()
+        .zip(&attrs)
+        .enumerate()
+        .find(|(_, (_, a))| a.unknown);
+    let mut ts = match unknown {
+        Some((i, (field, _))) => {
+            let member = field_member(i, field).0;
+            quote! {
+                let mut __unknown = ::std::iter::IntoIterator::into_iter(&self.#member).peekable();
+            }
+        }
+        None => TokenStream2::default(),
+    };
+    for (i, (field, attrs)) in fields.iter().zip(&attrs).enumerate() {
+        let tag = match attrs.tag {
+            Some(tag) => tag,
+            None => continue,
+        };
+        let (ident, _) = field_member(i, field);
+        let unknown_before = unknown.map(|_| {
+            quote! {
+                while let Some((tag, v)) = __unknown.next_if(|(tag, _)| **tag < #tag) {
+                    jcers::JcePut::jce_put(v, jce_mut, *tag);
+                }
+            }
+        });
+        let put = match &attrs.with {
+            Some(with) => quote!(#with::jce_put(v, jce_mut, #tag)),
+            None => quote!(jcers::JcePut::jce_put(v, jce_mut, #tag)),
+        };
+        // `None` is not written
+        let put = match option_inner(&field.ty) {
+            Some(_) if attrs.with.is_none() => quote! {
+                if let Some(v) = v {
+                    #put;
+                }
+            },
+            _ => quote!(#put;),
+        };
+        let put = match &attrs.skip_serializing_if {
+            Some(skip) => quote! {
+                if !#skip(v) {
+                    #put
+                }
+            },
+            None => put,
+        };
         ts = quote! {
             #ts
-            self.#ident.jce_put(jce_mut, #tag);
-        }
+            {
+                #unknown_before
+                let v = &self.#ident;
+                #put
+            }
+        };
     }
+    if unknown.is_some() {
+        ts.extend(quote! {
+            for (tag, v) in __unknown {
+                jcers::JcePut::jce_put(v, jce_mut, *tag);
+            }
+        });
+    }
+    let warning = tag_order_warning(&struct_name, &attrs);
     ts = quote! {
-        impl jcers::JcePut for #struct_name {
-            fn jce_put_raw(self, jce_mut: &mut jcers::JceMut) {
+        impl #impl_generics jcers::JcePut for #struct_name #ty_generics #where_clause {
+            fn jce_put_raw(&self, jce_mut: &mut jcers::JceMut) {
                 #ts
             }
         }
+
+        #warning
     };
     Ok(ts)
 }
 
+fn gen_get_transparent(
+    struct_name: Ident,
+    generics: Generics,
+    field: Field,
+) -> Result<TokenStream2, Vec<syn::Error>> {
+    let member = field_member(0, &field).0;
+    let ty = &field.ty;
+    let items = match is_borrowed(&generics) {
+        true => {
+            let de = de_lifetime();
+            quote! {
+                fn jce_get_ref(jce: &mut jcers::Jce<&#de [u8]>) -> jcers::JceResult<Self> {
+                    Ok(#struct_name {
+                        #member: <#ty as jcers::JceGetRef<#de>>::jce_get_ref(jce)?,
+                    })
+                }
+            }
+        }
+        false => quote! {
+            fn jce_get<B: bytes::Buf + ?Sized>(jce: &mut jcers::Jce<B>) -> jcers::JceResult<Self> {
+                Ok(#struct_name {
+                    #member: <#ty as jcers::JceGet>::jce_get(jce)?,
+                })
+            }
+
+            fn empty() -> jcers::JceResult<Self> {
+                Ok(#struct_name {
+                    #member: <#ty as jcers::JceGet>::empty()?,
+                })
+            }
+        },
+    };
+    Ok(get_impl(&struct_name, &generics, items))
+}
+
+fn gen_put_transparent(
+    struct_name: Ident,
+    generics: Generics,
+    field: Field,
+) -> Result<TokenStream2, Vec<syn::Error>> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let member = field_member(0, &field).0;
+    Ok(quote! {
+        impl #impl_generics jcers::JcePut for #struct_name #ty_generics #where_clause {
+            fn jce_put(&self, jce_mut: &mut jcers::JceMut, tag: u8) {
+                jcers::JcePut::jce_put(&self.#member, jce_mut, tag)
+            }
+
+            fn jce_put_raw(&self, jce_mut: &mut jcers::JceMut) {
+                jcers::JcePut::jce_put_raw(&self.#member, jce_mut)
+            }
+
+            fn freeze(&self) -> bytes::Bytes {
+                jcers::JcePut::freeze(&self.#member)
+            }
+        }
+    })
+}
+
+fn gen_get_enum(
+    enum_name: Ident,
+    generics: Generics,
+    variants: Vec<Ident>,
+) -> Result<TokenStream2, Vec<syn::Error>> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let enum_str = enum_name.to_string();
+    // a match on the value read, also used for an Empty head, which is 0
+    let from_i32 = quote! {
+        #(
+            v if v == #enum_name::#variants as i32 => Ok(#enum_name::#variants),
+        )*
+        v => Err(jcers::JceError::UnknownEnumValue(#enum_str, v)),
+    };
+    Ok(quote! {
+        impl #impl_generics jcers::JceGet for #enum_name #ty_generics #where_clause {
+            fn jce_get<B: bytes::Buf + ?Sized>(jce: &mut jcers::Jce<B>) -> jcers::JceResult<Self> {
+                match <i32 as jcers::JceGet>::jce_get(jce)? {
+                    #from_i32
+                }
+            }
+
+            fn empty() -> jcers::JceResult<Self> {
+                match 0i32 {
+                    #from_i32
+                }
+            }
+        }
+    })
+}
+
+fn gen_put_enum(
+    enum_name: Ident,
+    generics: Generics,
+    variants: Vec<Ident>,
+) -> Result<TokenStream2, Vec<syn::Error>> {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let value = quote! {
+        match self {
+            #(#enum_name::#variants => #enum_name::#variants as i32,)*
+        }
+    };
+    Ok(quote! {
+        impl #impl_generics jcers::JcePut for #enum_name #ty_generics #where_clause {
+            fn jce_put(&self, jce_mut: &mut jcers::JceMut, tag: u8) {
+                let value: i32 = #value;
+                jcers::JcePut::jce_put(&value, jce_mut, tag)
+            }
+
+            fn freeze(&self) -> bytes::Bytes {
+                let mut jce_mut = jcers::JceMut::new();
+                jcers::JcePut::jce_put(self, &mut jce_mut, 0);
+                jce_mut.freeze()
+            }
+        }
+    })
+}
+
+/// `JceTypeName` of the `name` given, or else of the struct name, the field
+/// of a transparent struct, and `int32` for an enum
+fn gen_type_name(input: Input, name: Option<syn::LitStr>) -> TokenStream2 {
+    let (ident, generics, value) = match (input, name) {
+        (
+            Input::Struct(ident, generics, _)
+            | Input::Transparent(ident, generics, _)
+            | Input::Enum(ident, generics, _),
+            Some(name),
+        ) => (ident, generics, quote!(#name.to_owned())),
+        (Input::Struct(ident, generics, _), None) => {
+            let name = ident.to_string();
+            (ident, generics, quote!(#name.to_owned()))
+        }
+        (Input::Transparent(ident, mut generics, field), None) => {
+            let ty = &field.ty;
+            let predicate: syn::WherePredicate = syn::parse_quote!(#ty: jcers::JceTypeName);
+            generics.make_where_clause().predicates.push(predicate);
+            (
+                ident,
+                generics,
+                quote!(<#ty as jcers::JceTypeName>::type_name()),
+            )
+        }
+        (Input::Enum(ident, generics, _), None) => (ident, generics, quote!("int32".to_owned())),
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics jcers::JceTypeName for #ident #ty_generics #where_clause {
+            fn type_name() -> ::std::string::String {
+                #value
+            }
+        }
+    }
+}
+
 fn to_compile_errors(errors: Vec<syn::Error>) -> proc_macro2::TokenStream {
     let errors = errors.into_iter().map(|e| e.to_compile_error());
     quote! {